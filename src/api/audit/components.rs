@@ -0,0 +1,66 @@
+//! Audit log route components.
+
+use rocket::FromForm;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::database::Id;
+
+/// Audit log filter query parameters.
+#[derive(Debug, FromForm, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuditFilter {
+    /// Only list events of this action.
+    #[param(example = "user.update")]
+    pub action: Option<String>,
+
+    /// Only list events performed by the user with this ID.
+    #[param(example = "3o8uj9c2q11i4pu0zgbc")]
+    pub actor: Option<String>,
+
+    /// Only list events affecting the user with this ID.
+    #[param(example = "3o8uj9c2q11i4pu0zgbc")]
+    pub target: Option<String>,
+
+    /// Only list events recorded at or after this time.
+    #[param(format = DateTime, example = "2024-04-13T00:00:00Z")]
+    pub since: Option<String>,
+
+    /// Only list events recorded before this time.
+    #[param(format = DateTime, example = "2024-04-14T00:00:00Z")]
+    pub until: Option<String>,
+}
+
+/// Audit event output body.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AuditEventOut {
+    pub id: Id<String>,
+
+    #[schema(example = "user.update")]
+    pub action: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actor: Option<Id<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<Id<String>>,
+
+    #[schema(example = "127.0.0.1")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip: Option<String>,
+
+    #[schema(example = "Mozilla/5.0 (X11; Linux x86_64; rv:125.0)")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+
+    /// Values before and after updates.
+    #[schema(example = json!({
+        "before": { "role": "user" }, "after": { "role": "admin" },
+    }))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<Value>,
+
+    #[schema(format = DateTime, example = "2024-04-13T12:25:04Z")]
+    pub created: String,
+}
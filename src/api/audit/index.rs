@@ -0,0 +1,67 @@
+//! Audit log listing route.
+
+use rocket::{get, http::Status, serde::json::Json};
+use surrealdb::sql::Datetime;
+
+use crate::database::Database;
+use super::{
+    super::{
+        login::{Login, Perm}, page::{Page, Paginated}, permission::AuditRead,
+        problem::Error,
+    },
+    components::{AuditEventOut, AuditFilter},
+};
+
+#[utoipa::path(
+    context_path = "/api/audit",
+    params(Page, AuditFilter),
+    responses(
+        (
+            status = 200, description = "Page of audit events",
+            body = PaginatedAuditEventOut,
+        ),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Insufficient privileges"),
+        (status = 422, description = "Invalid query parameters"),
+    ),
+    security(("login" = [])),
+    tag = "audit",
+)]
+
+/// GET /api/audit/?limit={limit}&offset={offset}&action={action}&actor={actor}&target={target}&since={since}&until={until}
+///
+/// List audit events page by page, newest first, optionally filtered by
+/// action, actor, target, and time range. Requires the `audit:read`
+/// permission.
+#[get("/?<filter..>")]
+pub async fn route(
+    _user: Login<Perm<AuditRead>>, db: &Database, page: Page,
+    filter: AuditFilter,
+) -> Result<Json<Paginated<AuditEventOut>>, Error> {
+    // parse time range
+    let parse = |value: &Option<String>| match value {
+        Some(value) => Datetime::try_from(value.as_str()).map(Some)
+            .map_err(|_| Error::from(Status::UnprocessableEntity)),
+        None => Ok(None),
+    };
+    let (since, until) = (parse(&filter.since)?, parse(&filter.until)?);
+
+    // query database for page of matching events and total count
+    let query = page.query(
+        "id, action, actor, target, ip, user_agent, diff, created",
+        "audit_event WHERE (!$action OR action = $action)
+            AND (!$actor OR actor = type::thing('user', $actor))
+            AND (!$target OR target = type::thing('user', $target))
+            AND (!$since OR created >= $since)
+            AND (!$until OR created < $until)",
+        "created DESC",
+    );
+    let events = page.fetch(db.query(query)
+        .bind(("action", &filter.action))
+        .bind(("actor", &filter.actor)).bind(("target", &filter.target))
+        .bind(("since", since)).bind(("until", until)),
+    ).await?;
+
+    // return page in envelope
+    Ok(Json(events))
+}
@@ -0,0 +1,68 @@
+//! Audit log of security-relevant events and routes for reading it.
+
+use rocket::{routes, Route};
+use serde::Serialize;
+use surrealdb::{engine::any::Any, method::Query, sql::Thing, Surreal};
+
+use super::client::Client;
+
+pub mod components;
+pub mod index;
+
+/// Assemble audit log routes.
+pub fn routes() -> Vec<Route> {
+    routes![index::route]
+}
+
+/// Statement recording the audit event bound as `$audit` unless `$audited`
+/// has been set to false. Statements may set `$actor`, `$target`, and `$diff`
+/// for values only known to the database.
+const RECORD: &str = "
+    IF $audited != false THEN (
+        CREATE audit_event SET
+            action = $audit.action, actor = $audit.actor ?? $actor,
+            target = $audit.target ?? $target, diff = $diff,
+            ip = $audit.ip, user_agent = $audit.user_agent
+    ) END;
+";
+
+/// Audit event to be recorded together with the change it describes.
+#[derive(Debug, Serialize)]
+pub struct Audit {
+    action: &'static str,
+    actor: Option<Thing>,
+    target: Option<Thing>,
+    ip: Option<String>,
+    user_agent: Option<String>,
+}
+
+impl Audit {
+    /// Create audit event for action requested by client.
+    pub fn new(action: &'static str, client: &Client) -> Self {
+        Self {
+            action, actor: None, target: None,
+            ip: client.ip.clone(), user_agent: client.user_agent.clone(),
+        }
+    }
+
+    /// Set user performing the action by their ID.
+    pub fn actor(mut self, id: &str) -> Self {
+        self.actor = Some(Thing::from(("user", id)));
+        self
+    }
+
+    /// Set user affected by the action by their ID.
+    pub fn target(mut self, id: &str) -> Self {
+        self.target = Some(Thing::from(("user", id)));
+        self
+    }
+
+    /// Query database with statements followed by recording the audit event,
+    /// all in one transaction.
+    pub fn query<'r>(
+        self, db: &'r Surreal<Any>, query: &str,
+    ) -> Query<'r, Any> {
+        db.query("BEGIN TRANSACTION").query(query).query(RECORD)
+            .query("COMMIT TRANSACTION").bind(("audit", self))
+    }
+}
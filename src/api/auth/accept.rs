@@ -0,0 +1,107 @@
+//! Invitation acceptance route.
+
+use rocket::{http::{CookieJar, Status}, post, serde::json::Json};
+use serde::Deserialize;
+use surrealdb::sql::Duration;
+use validator::Validate;
+
+use crate::{database::{Database, Take}, mail::Mail};
+use super::{
+    super::{
+        audit::Audit, client::Client, cookie, problem::{Context, Error},
+        Config,
+    },
+    components::{AcceptIn, LoginOut},
+};
+
+/// Database response type.
+#[derive(Deserialize)]
+struct DbOutput {
+    email_address: String,
+    login: LoginOut,
+}
+
+#[utoipa::path(
+    context_path = "/api/auth",
+    request_body = AcceptIn,
+    responses((
+        status = 200, description = "Invitation accepted", body = LoginOut
+    ), (
+        status = 404, description = "Invitation token not found",
+    ), (
+        status = 422, description = "Invalid token or password",
+    )),
+    tag = "authentication",
+)]
+
+/// POST /api/auth/accept
+///
+/// Accept invitation that has been sent by an admin within the past 7 days by
+/// choosing a password, and send a confirmation email. The new account is
+/// recorded in the audit log. With cookie sessions enabled, the session is
+/// also set as cookie.
+#[post("/accept", data = "<data>")]
+pub async fn route(
+    db: &Database, mail: &Mail, config: &Config, client: Client,
+    cookies: &CookieJar<'_>, data: Json<AcceptIn>,
+) -> Result<Json<LoginOut>, Error> {
+    // validate input
+    data.validate()?;
+
+    // query database to create invited user and login session
+    let audit = Audit::new("auth.accept", &client);
+    let result: Option<DbOutput> = audit.query(db, "
+        DELETE invitation WHERE expires < time::now();
+
+        let $data = (
+            DELETE invitation WHERE token = crypto::sha256($tok)
+            RETURN BEFORE
+        )[0].data;
+        let $audited = !!$data;
+
+        if $data {
+            let $uid = (
+                CREATE ONLY user SET
+                    name = $data.name, email = $data.email,
+                    role = $data.role,
+                    password = crypto::argon2::generate($pass)
+                RETURN id
+            ).id;
+
+            let $secret = rand::string();
+            let $login = (
+                CREATE ONLY login SET
+                    user = $uid, token = crypto::sha256($secret),
+                    expires = time::now() + $lifetime,
+                    ip = $ip, user_agent = $agent
+                RETURN
+                    user.id AS id, user.name AS name, user.role AS role,
+                    $secret AS token, expires
+            );
+
+            { \"email_address\": $data.email, \"login\": $login };
+        };
+
+        let $actor = (
+            SELECT VALUE id FROM ONLY user WHERE email = $data.email LIMIT 1
+        );
+    ").bind(("tok", &data.token)).bind(("pass", &data.password))
+        .bind(("lifetime", Duration::from_secs(config.session_lifetime)))
+        .bind(("ip", &client.ip)).bind(("agent", &client.user_agent))
+        .await.take(3)
+        .context("error executing invitation acceptance query")?;
+
+    // extract results or return not found
+    let DbOutput { email_address, login } = result.ok_or(Error::new(
+        Status::NotFound, "token_not_found", "Invitation token not found",
+    ))?;
+
+    // spawn job for sending email if acceptance successful
+    mail.spawn(&email_address, "confirm-account", &[("name", &login.name)]);
+
+    // set session cookie if enabled and return JSON response
+    if config.cookie_sessions {
+        cookie::set(cookies, &login.token, config.session_lifetime);
+    }
+    Ok(Json(login))
+}
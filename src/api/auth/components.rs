@@ -20,6 +20,12 @@ pub struct RegisterIn {
     #[schema(example = "supersecret")]
     #[validate(length(min = 8))]
     pub password: String,
+
+    /// Invite code required by the invite registration policy.
+    #[schema(example = "Ct6LXRBOcKKPdJAiiTKYb6NgQJWhxyLL")]
+    #[validate(length(min = 32, max = 32))]
+    #[serde(skip_serializing)]
+    pub invite: Option<String>,
 }
 
 /// Registration confirmation input body.
@@ -30,6 +36,26 @@ pub struct ConfirmIn {
     pub token: String,
 }
 
+/// Invitation acceptance input body.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct AcceptIn {
+    #[schema(example = "Ct6LXRBOcKKPdJAiiTKYb6NgQJWhxyLL")]
+    #[validate(length(min = 32, max = 32))]
+    pub token: String,
+
+    #[schema(example = "supersecret")]
+    #[validate(length(min = 8))]
+    pub password: String,
+}
+
+/// Account restore input body.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct RestoreIn {
+    #[schema(example = "Ct6LXRBOcKKPdJAiiTKYb6NgQJWhxyLL")]
+    #[validate(length(min = 32, max = 32))]
+    pub token: String,
+}
+
 /// Login input body.
 #[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct LoginIn {
@@ -55,4 +81,17 @@ pub struct LoginOut {
 
     #[schema(example = "Ct6LXRBOcKKPdJAiiTKYb6NgQJWhxyLL")]
     pub token: String,
+
+    #[schema(format = DateTime, example = "2024-05-13T12:25:04Z")]
+    pub expires: String,
+}
+
+/// Second factor challenge output body.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct ChallengeOut {
+    #[schema(example = "Ct6LXRBOcKKPdJAiiTKYb6NgQJWhxyLL")]
+    pub challenge: String,
+
+    #[schema(format = DateTime, example = "2024-04-13T12:30:04Z")]
+    pub expires: String,
 }
@@ -1,11 +1,18 @@
 //! Registration confirmation route.
 
-use rocket::{http::Status, post, serde::json::Json};
+use rocket::{http::{CookieJar, Status}, post, serde::json::Json};
 use serde::Deserialize;
+use surrealdb::sql::Duration;
 use validator::Validate;
 
-use crate::{database::Database, mail::Mail};
-use super::components::{ConfirmIn, LoginOut};
+use crate::{database::{Database, Take}, mail::Mail};
+use super::{
+    super::{
+        audit::Audit, client::Client, cookie, problem::{Context, Error},
+        Config,
+    },
+    components::{ConfirmIn, LoginOut},
+};
 
 /// Database response type.
 #[derive(Deserialize)]
@@ -21,6 +28,8 @@ struct DbOutput {
         status = 200, description = "Successful registration", body = LoginOut
     ), (
         status = 404, description = "Registration token not found",
+    ), (
+        status = 422, description = "Invalid token",
     )),
     tag = "authentication",
 )]
@@ -28,44 +37,64 @@ struct DbOutput {
 /// POST /api/auth/confirm
 ///
 /// Confirm registration that has been initiated within the past 30 minutes,
-/// and send a confirmation email.
+/// and send a confirmation email. The new account is recorded in the audit
+/// log. With cookie sessions enabled, the session is also set as cookie.
 #[post("/confirm", data = "<data>")]
 pub async fn route(
-    db: &Database, mail: &Mail, data: Json<ConfirmIn>
-) -> Result<Json<LoginOut>, Status> {
+    db: &Database, mail: &Mail, config: &Config, client: Client,
+    cookies: &CookieJar<'_>, data: Json<ConfirmIn>,
+) -> Result<Json<LoginOut>, Error> {
     // validate input
-    data.validate().map_err(|_| Status::UnprocessableEntity)?;
+    data.validate()?;
 
     // query database to confirm registration and create login session
-    let result: Option<DbOutput> = db.query("
+    let audit = Audit::new("auth.register", &client);
+    let result: Option<DbOutput> = audit.query(db, "
         DELETE registration WHERE expires < time::now();
 
         let $data = (
-            DELETE registration WHERE token = $tok RETURN BEFORE
+            DELETE registration WHERE token = crypto::sha256($tok)
+            RETURN BEFORE
         )[0].data;
+        let $audited = !!$data;
 
         if $data {
             let $uid = (CREATE ONLY user CONTENT $data RETURN id).id;
 
+            let $secret = rand::string();
             let $login = (
-                CREATE ONLY login SET user = $uid RETURN
-                user.id AS id, user.name AS name, user.role AS role, token
+                CREATE ONLY login SET
+                    user = $uid, token = crypto::sha256($secret),
+                    expires = time::now() + $lifetime,
+                    ip = $ip, user_agent = $agent
+                RETURN
+                    user.id AS id, user.name AS name, user.role AS role,
+                    $secret AS token, expires
             );
 
             { \"email_address\": $data.email, \"login\": $login };
         };
+
+        let $actor = (
+            SELECT VALUE id FROM ONLY user WHERE email = $data.email LIMIT 1
+        );
     ").bind(("tok", &data.token))
-        .await.and_then(|mut r| r.take(2))
-        .expect("error executing registration query");
+        .bind(("lifetime", Duration::from_secs(config.session_lifetime)))
+        .bind(("ip", &client.ip)).bind(("agent", &client.user_agent))
+        .await.take(3)
+        .context("error executing registration confirmation query")?;
 
     // extract results or return not found
-    let DbOutput { email_address, login } = result.ok_or(Status::NotFound)?;
+    let DbOutput { email_address, login } = result.ok_or(Error::new(
+        Status::NotFound, "token_not_found", "Registration token not found",
+    ))?;
 
-    // send email if confirmation successful
-    let email = mail.template("confirm-account", &[("name", &login.name)])
-        .await.expect("error rendering email template");
-    mail.send(&email_address, email).await.expect("error sending email");
+    // spawn job for sending email if confirmation successful
+    mail.spawn(&email_address, "confirm-account", &[("name", &login.name)]);
 
-    // return JSON response
+    // set session cookie if enabled and return JSON response
+    if config.cookie_sessions {
+        cookie::set(cookies, &login.token, config.session_lifetime);
+    }
     Ok(Json(login))
 }
@@ -0,0 +1,96 @@
+//! Email change route.
+
+use rocket::{http::Status, post, serde::json::Json};
+use serde::Deserialize;
+use validator::Validate;
+
+use crate::{database::{Database, Take}, mail::Mail};
+use super::{
+    super::super::{
+        audit::Audit, client::Client, login::{Direct, Login},
+        problem::{Context, Error},
+    },
+    components::EmailChangeIn,
+};
+
+/// Database response type.
+#[derive(Deserialize)]
+struct DbOutput {
+    token: String,
+    old_email: String,
+}
+
+#[utoipa::path(
+    context_path = "/api/auth",
+    request_body = EmailChangeIn,
+    responses(
+        (status = 204, description = "Email change maybe initiated"),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Impersonation session"),
+        (status = 422, description = "Invalid email address"),
+    ),
+    security(("login" = [])),
+    tag = "email change",
+)]
+
+/// POST /api/auth/email/change
+///
+/// Initiate change of the currently logged in user's email address, send a
+/// verification email to the new address and a notice to the old one. The
+/// response will not expose whether the new address is already in use to
+/// protect the users' privacy, in which case no emails are sent. A previously
+/// pending change is replaced. Unconfirmed changes expire after 30 minutes.
+/// Impersonation sessions cannot change the email address. Initiated changes
+/// are recorded in the audit log.
+#[post("/email/change", data = "<data>")]
+pub async fn route(
+    user: Login<Direct>, db: &Database, mail: &Mail, client: Client,
+    data: Json<EmailChangeIn>,
+) -> Result<Status, Error> {
+    // validate input
+    data.validate()?;
+
+    // query database to create email change and return confirmation token
+    let audit = Audit::new("auth.email_change_request", &client)
+        .actor(&user.id.0);
+    let result: Option<DbOutput> = audit.query(db, "
+        DELETE email_change
+        WHERE expires < time::now() OR user = type::thing('user', $uid);
+
+        let $existing = array::concat((
+            SELECT id FROM registration
+            WHERE data.email = string::lowercase($email)
+        ), (
+            SELECT id FROM invitation
+            WHERE data.email = string::lowercase($email)
+                AND expires > time::now()
+        ), (
+            SELECT id FROM user
+            WHERE email = string::lowercase($email)
+        ));
+        let $audited = !$existing;
+
+        let $secret = rand::string();
+
+        if !$existing then (
+            CREATE ONLY email_change SET
+                user = type::thing('user', $uid), email = $email,
+                token = crypto::sha256($secret),
+                expires = time::now() + 30m
+            RETURN $secret AS token, user.email AS old_email
+        ) end;
+    ").bind(("uid", &user.id.0)).bind(("email", &data.email))
+        .await.take(4)
+        .context("error executing email change query")?;
+
+    // spawn jobs for sending emails if change successfully initiated
+    if let Some(DbOutput { token, old_email }) = result {
+        mail.spawn(&data.email, "verify-email", &[("token", &token)]);
+        mail.spawn(&old_email, "email-changed", &[
+            ("name", &user.name), ("email", &data.email),
+        ]);
+    }
+
+    // return success status
+    Ok(Status::NoContent)
+}
@@ -0,0 +1,21 @@
+//! Email change route components.
+
+use serde::Deserialize;
+use utoipa::ToSchema;
+use validator::Validate;
+
+/// Email change input body.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct EmailChangeIn {
+    #[schema(example = "alice@example.org")]
+    #[validate(email)]
+    pub email: String,
+}
+
+/// Email change confirmation input body.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct EmailConfirmIn {
+    #[schema(example = "Ct6LXRBOcKKPdJAiiTKYb6NgQJWhxyLL")]
+    #[validate(length(min = 32, max = 32))]
+    pub token: String,
+}
@@ -0,0 +1,83 @@
+//! Email change confirmation route.
+
+use rocket::{http::Status, post, serde::json::Json};
+use serde::Deserialize;
+use validator::Validate;
+
+use crate::database::{Database, Take};
+use super::{
+    super::super::{audit::Audit, client::Client, problem::{Context, Error}},
+    components::EmailConfirmIn,
+};
+
+/// Database response type.
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum DbOutput {
+    Changed,
+    Conflict,
+}
+
+#[utoipa::path(
+    context_path = "/api/auth",
+    request_body = EmailConfirmIn,
+    responses(
+        (status = 204, description = "Email address changed"),
+        (status = 404, description = "Email change token not found"),
+        (status = 409, description = "Email address already in use"),
+        (status = 422, description = "Invalid token"),
+    ),
+    tag = "email change",
+)]
+
+/// POST /api/auth/email/confirm
+///
+/// Confirm email change that has been initiated within the past 30 minutes.
+/// The change is recorded in the audit log.
+#[post("/email/confirm", data = "<data>")]
+pub async fn route(
+    db: &Database, client: Client, data: Json<EmailConfirmIn>,
+) -> Result<Status, Error> {
+    // validate input
+    data.validate()?;
+
+    // query database to confirm email change unless address is taken
+    let audit = Audit::new("auth.email_change", &client);
+    let result: Option<DbOutput> = audit.query(db, "
+        DELETE email_change WHERE expires < time::now();
+
+        let $change = (
+            DELETE email_change WHERE token = crypto::sha256($tok)
+            RETURN BEFORE
+        )[0];
+        let $taken = !!(SELECT id FROM user WHERE email = $change.email);
+        let $audited = !!$change AND !$taken;
+        let $actor = $change.user;
+        let $diff = {
+            before: { email: $change.user.email },
+            after: { email: $change.email },
+        };
+
+        if !$change {
+            NONE;
+        } else if $taken {
+            'conflict';
+        } else {
+            UPDATE $change.user SET email = $change.email;
+            'changed';
+        };
+    ").bind(("tok", &data.token))
+        .await.take(6)
+        .context("error executing email change confirmation query")?;
+
+    // return success, conflict, or not found status
+    match result {
+        Some(DbOutput::Changed) => Ok(Status::NoContent),
+        Some(DbOutput::Conflict) => Err(Error::new(
+            Status::Conflict, "email_taken", "Email address already in use",
+        )),
+        None => Err(Error::new(
+            Status::NotFound, "token_not_found", "Email change token not found",
+        )),
+    }
+}
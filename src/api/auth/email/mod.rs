@@ -0,0 +1,5 @@
+//! Email change routes.
+
+pub mod components;
+pub mod change;
+pub mod confirm;
@@ -0,0 +1,64 @@
+//! API key route components.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use surrealdb::sql::Datetime;
+use validator::{Validate, ValidationError};
+
+use crate::database::Id;
+
+/// API key creation input body.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct KeyIn {
+    #[schema(example = "CI deployment")]
+    #[validate(length(min = 1, max = 64))]
+    pub name: String,
+
+    /// Permissions the key may use, as far as granted to the user's role.
+    #[schema(example = json!(["users:list"]))]
+    #[validate(custom(function = "super::super::super::permission::validate"))]
+    pub scopes: Vec<String>,
+
+    /// Optional expiry, after which the key is rejected and purged.
+    #[schema(format = DateTime, example = "2025-04-13T00:00:00Z")]
+    #[validate(custom(function = "validate_expires"))]
+    pub expires: Option<String>,
+}
+
+/// API key output body.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct KeyOut {
+    pub id: Id<String>,
+
+    #[schema(example = "CI deployment")]
+    pub name: String,
+
+    #[schema(example = json!(["users:list"]))]
+    pub scopes: Vec<String>,
+
+    #[schema(format = DateTime, example = "2024-04-13T12:25:04Z")]
+    pub created: String,
+
+    #[schema(format = DateTime, example = "2024-04-14T08:12:45Z")]
+    pub last_used: Option<String>,
+
+    #[schema(format = DateTime, example = "2025-04-13T00:00:00Z")]
+    pub expires: Option<String>,
+}
+
+/// Created API key output body including the token, which is only shown once.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct KeyCreatedOut {
+    #[serde(flatten)]
+    pub key: KeyOut,
+
+    #[schema(example = "key_Ct6LXRBOcKKPdJAiiTKYb6NgQJWhxyLL")]
+    pub token: String,
+}
+
+/// Validate expiry being a datetime in the future.
+fn validate_expires(value: &str) -> Result<(), ValidationError> {
+    Datetime::try_from(value).ok()
+        .filter(|expires| *expires > Datetime::default()).map(|_| ())
+        .ok_or(ValidationError::new("invalid_expires"))
+}
@@ -0,0 +1,72 @@
+//! API key creation route.
+
+use rocket::{http::Status, post, serde::json::Json};
+use validator::Validate;
+
+use crate::database::{Database, Take};
+use super::{
+    super::super::{
+        audit::Audit, client::Client, login::{Direct, Login, KEY_PREFIX},
+        problem::{Context, Error},
+    },
+    components::{KeyCreatedOut, KeyIn},
+};
+
+#[utoipa::path(
+    context_path = "/api/auth",
+    request_body = KeyIn,
+    responses(
+        (status = 201, description = "Created API key", body = KeyCreatedOut),
+        (status = 401, description = "Invalid login session"),
+        (
+            status = 403,
+            description = "Scope not granted or own session required",
+        ),
+        (status = 422, description = "Invalid API key data"),
+    ),
+    security(("login" = [])),
+    tag = "api keys",
+)]
+
+/// POST /api/auth/keys
+///
+/// Create named API key for the currently logged in user, which can be used
+/// in place of a session token in the authorization header until it expires
+/// or is revoked. Its permissions are those of the user's role limited to the
+/// given scopes, which must all be granted when creating it. The token is
+/// only returned once and stored hashed. Requires logging in with a session
+/// of one's own, and is recorded in the audit log.
+#[post("/keys", data = "<data>")]
+pub async fn route(
+    user: Login<Direct>, db: &Database, client: Client, data: Json<KeyIn>,
+) -> Result<(Status, Json<KeyCreatedOut>), Error> {
+    // validate input
+    data.validate()?;
+
+    // restrict scopes to permissions granted to the logged in user
+    data.scopes.iter().all(|scope| user.can(scope))
+        .then_some(()).ok_or(Status::Forbidden)?;
+
+    // query database to create API key
+    let audit = Audit::new("auth.key_create", &client).actor(&user.id.0);
+    let key: Option<KeyCreatedOut> = audit.query(db, "
+        let $secret = $prefix + rand::string();
+        let $expires = if $until then <datetime> $until end;
+        let $diff = {
+            after: { name: $name, scopes: $scopes, expires: $expires },
+        };
+
+        CREATE ONLY api_key SET
+            user = type::thing('user', $uid), name = $name, scopes = $scopes,
+            expires = $expires, token = crypto::sha256($secret)
+        RETURN id, name, scopes, created, last_used, expires, $secret AS token;
+    ").bind(("uid", &user.id.0)).bind(("name", &data.name))
+        .bind(("scopes", &data.scopes)).bind(("until", &data.expires))
+        .bind(("prefix", KEY_PREFIX))
+        .await.take(3)
+        .context("error creating API key")?;
+
+    // return created key with token
+    let key = key.context("created API key missing")?;
+    Ok((Status::Created, Json(key)))
+}
@@ -0,0 +1,48 @@
+//! API key revocation route.
+
+use rocket::{delete, http::Status};
+
+use crate::database::{Database, Record, Take};
+use super::super::super::{
+    audit::Audit, client::Client, login::{Direct, Login},
+    problem::{Context, Error},
+};
+
+#[utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 204, description = "API key revoked"),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Own session required"),
+        (status = 404, description = "API key not found"),
+    ),
+    security(("login" = [])),
+    tag = "api keys",
+)]
+
+/// DELETE /api/auth/keys/{id}
+///
+/// Revoke API key of the currently logged in user by its ID. Requires logging
+/// in with a session of one's own, and is recorded in the audit log.
+#[delete("/keys/<id>")]
+pub async fn route(
+    user: Login<Direct>, db: &Database, client: Client, id: &str,
+) -> Result<Status, Error> {
+    // delete key of logged in user
+    let audit = Audit::new("auth.key_revoke", &client).actor(&user.id.0);
+    let result: Option<Record> = audit.query(db, "
+        let $deleted = (
+            DELETE api_key
+            WHERE id = type::thing('api_key', $id)
+                AND user = type::thing('user', $uid)
+            RETURN BEFORE
+        );
+        let $audited = !!$deleted;
+        $deleted;
+    ").bind(("id", id)).bind(("uid", &user.id.0))
+        .await.take(2)
+        .context("error revoking API key")?;
+
+    // return success or not found status
+    result.map(|_| Status::NoContent).ok_or(Status::NotFound.into())
+}
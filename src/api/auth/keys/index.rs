@@ -0,0 +1,39 @@
+//! API key listing route.
+
+use rocket::{get, serde::json::Json};
+
+use crate::database::{Database, Take};
+use super::{
+    super::super::{login::{Direct, Login}, problem::{Context, Error}},
+    components::KeyOut,
+};
+
+#[utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 200, description = "List of API keys", body = Vec<KeyOut>),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Own session required"),
+    ),
+    security(("login" = [])),
+    tag = "api keys",
+)]
+
+/// GET /api/auth/keys
+///
+/// List API keys of the currently logged in user without exposing their
+/// tokens, newest first. Requires logging in with a session of one's own.
+#[get("/keys")]
+pub async fn route(
+    user: Login<Direct>, db: &Database,
+) -> Result<Json<Vec<KeyOut>>, Error> {
+    let keys: Vec<KeyOut> = db.query("
+        SELECT id, name, scopes, created, last_used, expires FROM api_key
+        WHERE user = type::thing('user', $uid)
+        ORDER BY created DESC
+    ").bind(("uid", &user.id.0))
+        .await.take(0)
+        .context("error retrieving API keys")?;
+
+    Ok(Json(keys))
+}
@@ -0,0 +1,6 @@
+//! API key routes.
+
+pub mod components;
+pub mod index;
+pub mod create;
+pub mod destroy;
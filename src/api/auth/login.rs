@@ -1,48 +1,193 @@
 //! Login route.
 
-use rocket::{http::Status, post, serde::json::Json};
+use rocket::{
+    http::{CookieJar, Status}, post, serde::json::Json, Responder,
+};
+use serde::Deserialize;
+use surrealdb::sql::Duration;
 use validator::Validate;
 
-use crate::database::Database;
-use super::components::{LoginIn, LoginOut};
+use crate::{database::{Database, Take}, mail::Mail};
+use super::{
+    super::{
+        audit::Audit, client::Client, cookie, problem::{Context, Error},
+        throttle::Throttle, users::components::DisabledOut, Config,
+    },
+    components::{ChallengeOut, LoginIn, LoginOut},
+};
+
+/// Database response type.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DbOutput {
+    Login(LoginOut),
+    Challenge(ChallengeOut),
+    Suspended { disabled: DisabledOut },
+    Deleted { deleted: String },
+}
+
+/// Login response with session or second factor challenge.
+#[derive(Responder)]
+pub enum LoginResponse {
+    #[response(status = 200)]
+    Login(Json<LoginOut>),
+
+    #[response(status = 202)]
+    Challenge(Json<ChallengeOut>),
+}
 
 #[utoipa::path(
     context_path = "/api/auth",
     request_body = LoginIn,
     responses(
         (status = 200, description = "Login successful", body = LoginOut),
+        (
+            status = 202, description = "Second factor required",
+            body = ChallengeOut,
+        ),
         (status = 401, description = "Invalid login data"),
+        (status = 403, description = "Account suspended or deleted"),
+        (status = 422, description = "Invalid email or password format"),
+        (status = 429, description = "Too many failed login attempts"),
     ),
     tag = "authentication",
 )]
 
 /// POST /api/auth/login
 ///
-/// Create login session for already registered user.
+/// Create login session for already registered user. Sessions expire after
+/// the configured lifetime or when unused for the configured idle timeout.
+///
+/// If the user has enabled two-factor authentication, a challenge valid for 5
+/// minutes is returned instead, which needs to be posted to
+/// `/api/auth/2fa/verify` together with a code to create the login session.
+///
+/// Suspended users are rejected after their password has been verified, with
+/// the reason and end of the suspension in the error detail. So are users
+/// pending deletion, who need to restore their account first.
+///
+/// Attempts are counted per email address and client IP until a login
+/// session is created, so that correct passwords followed by invalid second
+/// factor codes keep counting. Once the configured limit is reached, further attempts are rejected for a
+/// lockout period doubling with each failure, and the account holder is
+/// notified. The client IP is only taken from the configured header of a
+/// trusted reverse proxy, and from the socket address otherwise.
+///
+/// With cookie sessions enabled, the session is also set as cookie for
+/// browser frontends.
+///
+/// Created sessions and failed attempts are recorded in the audit log.
 #[post("/login", data = "<data>")]
 pub async fn route(
-    db: &Database, data: Json<LoginIn>,
-) -> Result<Json<LoginOut>, Status> {
+    db: &Database, config: &Config, mail: &Mail, client: Client,
+    cookies: &CookieJar<'_>, data: Json<LoginIn>,
+) -> Result<LoginResponse, Error> {
     // validate input
-    data.validate().map_err(|_| Status::UnprocessableEntity)?;
+    data.validate()?;
+
+    // count attempt and reject it while email address or client IP are locked
+    let mut throttle = Throttle::new(&data.email, &client);
+    throttle.attempt(db, config).await?;
+
+    // query database to validate credentials and create login or challenge
+    let audit = Audit::new("auth.login", &client);
+    let result: Option<DbOutput> = audit.query(db, "
+        DELETE login
+        WHERE expires < time::now() OR last_used < time::now() - $idle;
+        DELETE login_challenge WHERE expires < time::now();
 
-    // query database to validate credentials and create login
-    let result: Option<LoginOut> = db.query("
         let $user = (
-            SELECT id FROM ONLY user
-            WHERE email = $email 
+            SELECT id, totp_secret, disabled, deleted FROM ONLY user
+            WHERE email = $email
                 AND crypto::argon2::compare(password, $pass)
             LIMIT 1
         );
 
-        if $user then (
-            CREATE ONLY login SET user = $user.id
-            RETURN user AS id, user.name AS name, user.role AS role, token
-        ) end;
+        let $secret = rand::string();
+        let $suspended = $user.disabled AND (
+            !$user.disabled.until OR $user.disabled.until > time::now()
+        );
+        let $audited = !!$user AND !$user.deleted AND !$suspended
+            AND !$user.totp_secret;
+        let $actor = $user.id;
+
+        if !$user {
+            NONE;
+        } else if $user.deleted {
+            { \"deleted\": $user.deleted };
+        } else if $suspended {
+            { \"disabled\": $user.disabled };
+        } else if $user.totp_secret {
+            (CREATE ONLY login_challenge SET
+                user = $user.id, token = crypto::sha256($secret),
+                expires = time::now() + 5m
+            RETURN $secret AS challenge, expires);
+        } else {
+            (CREATE ONLY login SET
+                user = $user.id, token = crypto::sha256($secret),
+                expires = time::now() + $lifetime,
+                ip = $ip, user_agent = $agent
+            RETURN
+                user AS id, user.name AS name, user.role AS role,
+                $secret AS token, expires);
+        };
     ").bind(("email", &data.email)).bind(("pass", &data.password))
-        .await.and_then(|mut r| r.take(1))
-        .expect("error executing login query");
+        .bind(("lifetime", Duration::from_secs(config.session_lifetime)))
+        .bind(("idle", Duration::from_secs(config.session_idle_timeout)))
+        .bind(("ip", &client.ip)).bind(("agent", &client.user_agent))
+        .await.take(7)
+        .context("error executing login query")?;
+
+    // record failure, notify account holder on lockout, and return error
+    let Some(result) = result else {
+        Audit::new("auth.login_failed", &client).query(db, "
+            let $target = (
+                SELECT VALUE id FROM ONLY user WHERE email = $email LIMIT 1
+            );
+        ").bind(("email", &data.email))
+            .await.check()
+            .context("error recording failed login")?;
+        if throttle.fail(config) {
+            let name: Option<String> = db.query("
+                SELECT VALUE name FROM ONLY user WHERE email = $email LIMIT 1
+            ").bind(("email", &data.email))
+                .await.take(0)
+                .context("error executing user query")?;
+            if let Some(name) = name {
+                mail.spawn(&data.email, "account-locked", &[("name", &name)]);
+            }
+        }
+
+        return Err(Error::new(
+            Status::Unauthorized, "invalid_credentials",
+            "Invalid email or password",
+        ));
+    };
 
-    // return json response or unauthorized error
-    result.map(Json).ok_or(Status::Unauthorized)
+    // forget failed attempts after full login and return login or challenge
+    match result {
+        DbOutput::Login(login) => {
+            throttle.reset(db, config).await?;
+            if config.cookie_sessions {
+                cookie::set(cookies, &login.token, config.session_lifetime);
+            }
+            Ok(LoginResponse::Login(Json(login)))
+        },
+        DbOutput::Challenge(challenge) =>
+            Ok(LoginResponse::Challenge(Json(challenge))),
+        DbOutput::Deleted { deleted } => Err(Error::new(
+            Status::Forbidden, "account_deleted", &format!(
+                "Account pending deletion since {deleted}, restore it with \
+                the emailed token",
+            ),
+        )),
+        DbOutput::Suspended { disabled } => {
+            let until = disabled.until.as_deref().unwrap_or("further notice");
+            Err(Error::new(
+                Status::Forbidden, "account_suspended", &format!(
+                    "Account suspended until {until}: {}", disabled.reason,
+                ),
+            ))
+        },
+    }
 }
@@ -1,9 +1,12 @@
 //! Logout route.
 
-use rocket::{http::Status, post};
+use rocket::{http::{CookieJar, Status}, post};
 
-use crate::database::Database;
-use super::super::login::{Login, User};
+use crate::database::{Database, Take};
+use super::super::{
+    audit::Audit, client::Client, cookie, login::{Login, User},
+    problem::{Context, Error},
+};
 
 #[utoipa::path(
     context_path = "/api/auth",
@@ -17,20 +20,35 @@ use super::super::login::{Login, User};
 
 /// POST /api/auth/logout
 ///
-/// Destroy login session identified via API token in authorization header.
+/// Destroy login session identified via API token in authorization header or
+/// session cookie, and clear session cookies if present. The logout is
+/// recorded in the audit log, as the end of the impersonation for sessions
+/// issued for impersonating the user.
 #[post("/logout")]
-pub async fn route(db: &Database, user: Login<User>) -> Status {
+pub async fn route(
+    db: &Database, client: Client, cookies: &CookieJar<'_>, user: Login<User>,
+) -> Result<Status, Error> {
     // delete login from database
-    let result: Option<bool> = db.query("
-        if (
-            DELETE login WHERE token = $tok RETURN id
+    let audit = match &user.impersonator {
+        Some(impersonator) => Audit::new("user.impersonate_stop", &client)
+            .actor(&impersonator.0).target(&user.id.0),
+        None => Audit::new("auth.logout", &client).actor(&user.id.0),
+    };
+    let result: Option<bool> = audit.query(db, "
+        let $audited = if (
+            DELETE type::thing('login', $sid) RETURN BEFORE
         ) then true else false end;
-    ").bind(("tok", &user.token))
-        .await.and_then(|mut r| r.take(0))
-        .expect("error executing logout query");
+        $audited;
+    ").bind(("sid", &user.session.0))
+        .await.take(1)
+        .context("error executing logout query")?;
 
-    let result = result.expect("error fetching logout query result");
-
-    // return success or unauthorized error
-    if result { Status::NoContent } else { Status::Unauthorized }
+    // clear session cookies and return success, or unauthorized error
+    match result.unwrap_or(false) {
+        true => {
+            cookie::clear(cookies);
+            Ok(Status::NoContent)
+        },
+        false => Err(Status::Unauthorized.into()),
+    }
 }
@@ -0,0 +1,41 @@
+//! Logout of other sessions route.
+
+use rocket::{http::Status, post};
+
+use crate::database::{Database, Take};
+use super::super::{
+    audit::Audit, client::Client, login::{Direct, Login},
+    problem::{Context, Error},
+};
+
+#[utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 204, description = "Other sessions revoked"),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Impersonation session"),
+    ),
+    security(("login" = [])),
+    tag = "sessions",
+)]
+
+/// POST /api/auth/logout-all
+///
+/// Destroy all login sessions of the currently logged in user except for the
+/// one used for this request. The revocation is recorded in the audit log.
+/// Impersonation sessions cannot revoke the user's other sessions.
+#[post("/logout-all")]
+pub async fn route(
+    db: &Database, client: Client, user: Login<Direct>,
+) -> Result<Status, Error> {
+    let audit = Audit::new("auth.logout_all", &client).actor(&user.id.0);
+    audit.query(db, "
+        DELETE login
+        WHERE user = type::thing('user', $uid)
+            AND id != type::thing('login', $sid)
+    ").bind(("uid", &user.id.0)).bind(("sid", &user.session.0))
+        .await.check()
+        .context("error revoking sessions")?;
+
+    Ok(Status::NoContent)
+}
@@ -5,16 +5,29 @@ use rocket::{routes, Route};
 pub mod components;
 pub mod register;
 pub mod confirm;
+pub mod accept;
+pub mod restore;
 pub mod login;
 pub mod logout;
+pub mod logout_all;
+pub mod sessions;
+pub mod keys;
 pub mod password;
+pub mod email;
+pub mod two_factor;
 
 /// Assemble authentication routes.
 pub fn routes() -> Vec<Route> {
     routes![
-        register::route, confirm::route,
-        login::route, logout::route,
+        register::route, confirm::route, accept::route, restore::route,
+        login::route, logout::route, logout_all::route,
+        sessions::index::route, sessions::destroy::route,
+        keys::index::route, keys::create::route, keys::destroy::route,
         password::reset::route, password::confirm::route,
+        password::change::route,
+        email::change::route, email::confirm::route,
+        two_factor::setup::route, two_factor::enable::route,
+        two_factor::disable::route, two_factor::verify::route,
     ]
 }
 
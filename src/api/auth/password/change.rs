@@ -0,0 +1,82 @@
+//! Password change route.
+
+use rocket::{http::Status, post, serde::json::Json};
+use validator::Validate;
+
+use crate::{database::{Database, Take}, mail::Mail};
+use super::{
+    super::super::{
+        audit::Audit, client::Client, login::{Direct, Login},
+        problem::{Context, Error},
+    },
+    components::PasswordChangeIn,
+};
+
+#[utoipa::path(
+    context_path = "/api/auth",
+    request_body = PasswordChangeIn,
+    responses(
+        (status = 204, description = "Password changed"),
+        (status = 401, description = "Invalid login session"),
+        (
+            status = 403,
+            description = "Invalid password or impersonation session",
+        ),
+        (status = 422, description = "Invalid password format"),
+    ),
+    security(("login" = [])),
+    tag = "password reset",
+)]
+
+/// POST /api/auth/password/change
+///
+/// Change password of the currently logged in user after verifying the
+/// current password, optionally revoke all other login sessions, and send a
+/// notification email. The change is recorded in the audit log. Not
+/// available in impersonation sessions.
+#[post("/password/change", data = "<data>")]
+pub async fn route(
+    user: Login<Direct>, db: &Database, mail: &Mail, client: Client,
+    data: Json<PasswordChangeIn>,
+) -> Result<Status, Error> {
+    // validate input
+    data.validate()?;
+
+    // query database to verify current password and update it
+    let audit = Audit::new("auth.password_change", &client).actor(&user.id.0);
+    let result: Option<String> = audit.query(db, "
+        let $email = (
+            SELECT VALUE email FROM ONLY type::thing('user', $uid)
+            WHERE crypto::argon2::compare(password, $current)
+        );
+        let $audited = !!$email;
+
+        if $email {
+            UPDATE type::thing('user', $uid)
+            SET password = crypto::argon2::generate($pass);
+
+            if $others {
+                DELETE login WHERE user = type::thing('user', $uid)
+                    AND id != type::thing('login', $sid);
+            };
+
+            $email;
+        };
+    ").bind(("uid", &user.id.0)).bind(("sid", &user.session.0))
+        .bind(("current", &data.current_password))
+        .bind(("pass", &data.password))
+        .bind(("others", data.logout_others))
+        .await.take(2)
+        .context("error executing password change query")?;
+
+    // return forbidden error if current password is incorrect
+    let email = result.ok_or(Error::new(
+        Status::Forbidden, "invalid_password", "Current password is incorrect",
+    ))?;
+
+    // spawn job for sending notification email
+    mail.spawn(&email, "password-changed", &[("name", &user.name)]);
+
+    // return success status
+    Ok(Status::NoContent)
+}
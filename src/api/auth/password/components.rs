@@ -22,3 +22,18 @@ pub struct PasswordConfirmIn {
     pub password: String,
 }
 
+
+/// Password change input body.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct PasswordChangeIn {
+    #[schema(example = "supersecret")]
+    pub current_password: String,
+
+    #[schema(example = "othersecret")]
+    #[validate(length(min = 8))]
+    pub password: String,
+
+    #[schema(example = true)]
+    #[serde(default)]
+    pub logout_others: bool,
+}
@@ -3,8 +3,11 @@
 use rocket::{http::Status, post, serde::json::Json};
 use validator::Validate;
 
-use crate::database::Database;
-use super::components::PasswordConfirmIn;
+use crate::database::{Database, Take};
+use super::{
+    super::super::{audit::Audit, client::Client, problem::{Context, Error}},
+    components::PasswordConfirmIn,
+};
 
 #[utoipa::path(
     context_path = "/api/auth",
@@ -12,6 +15,7 @@ use super::components::PasswordConfirmIn;
     responses(
         (status = 204, description = "Reset successful"),
         (status = 404, description = "Reset token not found"),
+        (status = 422, description = "Invalid token or password"),
     ),
     tag = "password reset",
 )]
@@ -19,18 +23,25 @@ use super::components::PasswordConfirmIn;
 /// POST /api/auth/password/confirm
 ///
 /// Confirm password reset that has been initiated within the past 30 minutes.
+/// The reset is recorded in the audit log.
 #[post("/password/confirm", data = "<data>")]
-pub async fn route(db: &Database, data: Json<PasswordConfirmIn>) -> Status {
+pub async fn route(
+    db: &Database, client: Client, data: Json<PasswordConfirmIn>,
+) -> Result<Status, Error> {
     // validate input
-    if let Err(_) = data.validate() { return Status::UnprocessableEntity; }
+    data.validate()?;
 
     // query database to confirm registration
-    let result: Option<bool> = db.query("
+    let audit = Audit::new("auth.password_reset", &client);
+    let result: Option<bool> = audit.query(db, "
         DELETE password_reset WHERE expires < time::now();
 
         let $uid = (
-            DELETE password_reset WHERE token = $tok RETURN BEFORE
+            DELETE password_reset WHERE token = crypto::sha256($tok)
+            RETURN BEFORE
         )[0].user;
+        let $audited = !!$uid;
+        let $actor = $uid;
 
         if $uid {
             DELETE login WHERE user = $uid;
@@ -40,12 +51,14 @@ pub async fn route(db: &Database, data: Json<PasswordConfirmIn>) -> Status {
             false;
         };
     ").bind(("tok", &data.token)).bind(("pass", &data.password))
-        .await.and_then(|mut r| r.take(2))
-        .expect("error executing password reset confirmation query");
+        .await.take(4)
+        .context("error executing password reset confirmation query")?;
 
-    let success = result
-        .expect("error fetching data from password reset confirmation query");
-
-    // return success or not found status
-    if success { Status::NoContent } else { Status::NotFound }
+    // return success or not found error
+    match result.unwrap_or(false) {
+        true => Ok(Status::NoContent),
+        false => Err(Error::new(
+            Status::NotFound, "token_not_found", "Reset token not found",
+        )),
+    }
 }
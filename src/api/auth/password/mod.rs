@@ -3,3 +3,4 @@
 pub mod components;
 pub mod reset;
 pub mod confirm;
+pub mod change;
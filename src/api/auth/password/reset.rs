@@ -3,15 +3,21 @@
 use rocket::{http::Status, post, serde::json::Json};
 use validator::Validate;
 
-use crate::{database::Database, mail::{Mail, Mailer}};
-use super::{super::super::pow::POW, components::ResetIn};
+use crate::{database::{Database, Take}, mail::Mail};
+use super::{
+    super::super::{
+        pow::{PasswordReset, POW}, problem::{Context, Error},
+    },
+    components::ResetIn,
+};
 
 #[utoipa::path(
     context_path = "/api/auth",
     request_body = ResetIn,
     responses(
         (status = 204, description = "Reset maybe successful"),
-        (status = 402, description = "Invalid proof of work"),
+        (status = 402, description = "Invalid proof of work or challenge"),
+        (status = 422, description = "Invalid email address"),
     ),
     tag = "password reset",
 )]
@@ -21,17 +27,15 @@ use super::{super::super::pow::POW, components::ResetIn};
 /// Initiate password reset and send verification email if account with email
 /// address exists and no password reset from within the last 30 minutes is
 /// pending. The response will not expose whether this is the case to protect
-/// the users' privacy. The request body needs to deliver a proof of work by
-/// making sure the binary representation of its SHA512 hash begins with 16
-/// zeros to achieve some protection against abuse by spammers. The example
-/// request body will e.g. be accepted if `"nonce": 77761` is added as its last
-/// field.
+/// the users' privacy. The request body needs to deliver a proof of work on a
+/// challenge from `/api/pow/challenge?scope=password_reset` to achieve some
+/// protection against abuse by spammers.
 #[post("/password/reset", data = "<data>")]
 pub async fn route(
-    db: &Database, mail: &Mail, data: POW<Json<ResetIn>>,
-) -> Status {
+    db: &Database, mail: &Mail, data: POW<Json<ResetIn>, PasswordReset>,
+) -> Result<Status, Error> {
     // validate input
-    if let Err(_) = data.validate() { return Status::UnprocessableEntity; }
+    data.validate()?;
 
     // query database to create password reset and return confirmation token
     let result: Option<String> = db.query("
@@ -44,25 +48,23 @@ pub async fn route(
             ).user LIMIT 1
         ).id;
 
+        let $secret = rand::string();
+
         if $uid then (
-            CREATE ONLY password_reset
-            SET user = $uid, expires = time::now() + 30m
-            RETURN token
+            CREATE ONLY password_reset SET
+                user = $uid, token = crypto::sha256($secret),
+                expires = time::now() + 30m
+            RETURN $secret AS token
         ).token end;
     ").bind(("email", &data.email))
-        .await.and_then(|mut r| r.take(2))
-        .expect("error executing password reset query");
+        .await.take(3)
+        .context("error executing password reset query")?;
 
     // spawn job for sending email if reset successfully initiated
-    let mail: Mailer = mail.inner().clone();
-    tokio::spawn(async move {
-        if let Some(token) = result {
-            let email = mail.template("reset-password", &[("token", &token)])
-                .await.expect("error rendering email template");
-            mail.send(&data.email, email).await.expect("error sending email");
-        }
-    });
+    if let Some(token) = result {
+        mail.spawn(&data.email, "reset-password", &[("token", &token)]);
+    }
 
     // return success status
-    Status::NoContent
+    Ok(Status::NoContent)
 }
@@ -3,15 +3,22 @@
 use rocket::{http::Status, post, serde::json::Json};
 use validator::Validate;
 
-use crate::{database::Database, mail::{Mail, Mailer}};
-use super::{super::pow::POW, components::RegisterIn};
+use crate::{
+    config::RegistrationPolicy, database::{Database, Record, Take}, mail::Mail,
+};
+use super::{
+    super::{pow::{Register, POW}, problem::{Context, Error}, Config},
+    components::RegisterIn,
+};
 
 #[utoipa::path(
     context_path = "/api/auth",
     request_body = RegisterIn,
     responses(
         (status = 204, description = "Registration maybe successful"),
-        (status = 402, description = "Invalid proof of work"),
+        (status = 402, description = "Invalid proof of work or challenge"),
+        (status = 403, description = "Registration not allowed by policy"),
+        (status = 422, description = "Invalid registration data"),
     ),
     tag = "authentication",
 )]
@@ -23,16 +30,24 @@ use super::{super::pow::POW, components::RegisterIn};
 /// that is the case to protect the users' privacy. Unconfirmed registrations 
 /// expire after 30 minutes.
 ///
-/// The request body needs to deliver a proof of work by making sure the
-/// binary representation of its SHA512 hash begins with 16 zeros to achieve
-/// some protection against abuse by spammers. The example request body will
-/// e.g. be accepted if `"nonce": 85223` is added as its last field.
+/// The request body needs to deliver a proof of work on a challenge from
+/// `/api/pow/challenge?scope=register` to achieve some protection against
+/// abuse by spammers.
+///
+/// Depending on the configured registration policy, registration may be
+/// closed, restricted to email addresses of allowed domains, or require an
+/// invite code created by an admin, which is used up even if the email
+/// address is already registered.
 #[post("/register", data = "<data>")]
 pub async fn route(
-    db: &Database, mail: &Mail, data: POW<Json<RegisterIn>>
-) -> Status {
+    db: &Database, mail: &Mail, config: &Config,
+    data: POW<Json<RegisterIn>, Register>,
+) -> Result<Status, Error> {
     // validate input
-    if let Err(_) = data.validate() { return Status::UnprocessableEntity; }
+    data.validate()?;
+
+    // enforce registration policy independently of existing accounts
+    policy(db, config, &data).await?;
 
     // query database to create registration and return confirmation token
     let result: Option<String> = db.query("
@@ -41,30 +56,73 @@ pub async fn route(
         let $existing = array::concat((
             SELECT id FROM registration
             WHERE data.email = string::lowercase($data.email)
+        ), (
+            SELECT id FROM invitation
+            WHERE data.email = string::lowercase($data.email)
+                AND expires > time::now()
         ), (
             SELECT id FROM user
             WHERE email = string::lowercase($data.email)
         ));
 
+        let $secret = rand::string();
+
         if !$existing then (
-            CREATE ONLY registration
-            SET data = $data, expires = time::now() + 30m
-            RETURN token
+            CREATE ONLY registration SET
+                data = $data, token = crypto::sha256($secret),
+                expires = time::now() + 30m
+            RETURN $secret AS token
         ).token end;
     ").bind(("data", &**data))
-        .await.and_then(|mut r| r.take(2))
-        .expect("error executing registration query");
+        .await.take(3)
+        .context("error executing registration query")?;
 
     // spawn job for sending email if registration successful
-    let mail: Mailer = mail.inner().clone();
-    tokio::spawn(async move {
-        if let Some(token) = result {
-            let email = mail.template("verify-account", &[("token", &token)])
-                .await.expect("error rendering email template");
-            mail.send(&data.email, email).await.expect("error sending email");
-        }
-    });
+    if let Some(token) = result {
+        mail.spawn(&data.email, "verify-account", &[("token", &token)]);
+    }
 
     // return success status
-    Status::NoContent
+    Ok(Status::NoContent)
+}
+
+/// Check whether registration policy allows registration.
+async fn policy(
+    db: &Database, config: &Config, data: &RegisterIn,
+) -> Result<(), Error> {
+    match config.registration {
+        RegistrationPolicy::Open => Ok(()),
+        RegistrationPolicy::Closed => Err(Error::new(
+            Status::Forbidden, "registration_closed", "Registration is closed",
+        )),
+        RegistrationPolicy::Domains => {
+            // compare domain of email address with allowed ones
+            let domain = data.email.rsplit_once('@')
+                .map_or("", |(_, domain)| domain);
+            let allowed = config.registration_domains.iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(domain));
+            allowed.then_some(()).ok_or(Error::new(
+                Status::Forbidden, "domain_not_allowed",
+                "Email domain not allowed to register",
+            ))
+        },
+        RegistrationPolicy::Invite => {
+            // use up invite code if still valid
+            let result: Option<Record> = match &data.invite {
+                Some(code) => db.query("
+                    UPDATE invite_code SET uses += 1
+                    WHERE token = crypto::sha256($code)
+                        AND expires > time::now() AND uses < max_uses
+                    RETURN id
+                ").bind(("code", code))
+                    .await.take(0)
+                    .context("error using invite code")?,
+                None => None,
+            };
+            result.map(|_| ()).ok_or(Error::new(
+                Status::Forbidden, "invalid_invite_code",
+                "Invite code invalid, expired, or used up",
+            ))
+        },
+    }
 }
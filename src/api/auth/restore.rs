@@ -0,0 +1,64 @@
+//! Account restore route.
+
+use rocket::{http::Status, post, serde::json::Json};
+use validator::Validate;
+
+use crate::database::{Database, Take};
+use super::{
+    super::{audit::Audit, client::Client, problem::{Context, Error}},
+    components::RestoreIn,
+};
+
+#[utoipa::path(
+    context_path = "/api/auth",
+    request_body = RestoreIn,
+    responses(
+        (status = 204, description = "Account restored"),
+        (status = 404, description = "Restore token not found"),
+        (status = 422, description = "Invalid token"),
+    ),
+    tag = "authentication",
+)]
+
+/// POST /api/auth/restore
+///
+/// Restore account pending deletion using the token sent by email, before
+/// the configured grace period has passed. The restore is recorded in the
+/// audit log.
+#[post("/restore", data = "<data>")]
+pub async fn route(
+    db: &Database, client: Client, data: Json<RestoreIn>,
+) -> Result<Status, Error> {
+    // validate input
+    data.validate()?;
+
+    // query database to unmark user as deleted
+    let audit = Audit::new("auth.restore", &client);
+    let result: Option<bool> = audit.query(db, "
+        DELETE account_restore WHERE expires < time::now();
+
+        let $uid = (
+            DELETE account_restore WHERE token = crypto::sha256($tok)
+            RETURN BEFORE
+        )[0].user;
+        let $audited = !!$uid;
+        let $actor = $uid;
+
+        if $uid {
+            UPDATE $uid SET deleted = NONE;
+            true;
+        } else {
+            false;
+        };
+    ").bind(("tok", &data.token))
+        .await.take(4)
+        .context("error executing account restore query")?;
+
+    // return success or not found error
+    match result.unwrap_or(false) {
+        true => Ok(Status::NoContent),
+        false => Err(Error::new(
+            Status::NotFound, "token_not_found", "Restore token not found",
+        )),
+    }
+}
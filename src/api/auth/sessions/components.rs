@@ -0,0 +1,34 @@
+//! Login session route components.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::database::Id;
+
+/// Login session output body.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SessionOut {
+    pub id: Id<String>,
+
+    #[schema(format = DateTime, example = "2024-04-13T12:25:04Z")]
+    pub created: String,
+
+    #[schema(format = DateTime, example = "2024-04-14T08:12:45Z")]
+    pub last_used: String,
+
+    #[schema(format = DateTime, example = "2024-05-13T12:25:04Z")]
+    pub expires: String,
+
+    #[schema(example = "127.0.0.1")]
+    pub ip: Option<String>,
+
+    #[schema(example = "Mozilla/5.0 (X11; Linux x86_64; rv:125.0)")]
+    pub user_agent: Option<String>,
+
+    /// User impersonating the session owner, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub impersonator: Option<Id<String>>,
+
+    #[schema(example = true)]
+    pub current: bool,
+}
@@ -0,0 +1,47 @@
+//! Session revocation route.
+
+use rocket::{delete, http::Status};
+
+use crate::database::{Database, Record, Take};
+use super::super::super::{
+    audit::Audit, client::Client, login::{Login, User},
+    problem::{Context, Error},
+};
+
+#[utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (status = 204, description = "Session revoked"),
+        (status = 404, description = "Session not found"),
+        (status = 401, description = "Invalid login session"),
+    ),
+    security(("login" = [])),
+    tag = "sessions",
+)]
+
+/// DELETE /api/auth/sessions/{id}
+///
+/// Revoke login session of the currently logged in user by its ID. The
+/// revocation is recorded in the audit log.
+#[delete("/sessions/<id>")]
+pub async fn route(
+    user: Login<User>, db: &Database, client: Client, id: &str,
+) -> Result<Status, Error> {
+    // delete session of logged in user
+    let audit = Audit::new("auth.session_revoke", &client).actor(&user.id.0);
+    let result: Option<Record> = audit.query(db, "
+        let $deleted = (
+            DELETE login
+            WHERE id = type::thing('login', $sid)
+                AND user = type::thing('user', $uid)
+            RETURN BEFORE
+        );
+        let $audited = !!$deleted;
+        $deleted;
+    ").bind(("sid", id)).bind(("uid", &user.id.0))
+        .await.take(2)
+        .context("error revoking session")?;
+
+    // return success or not found status
+    result.map(|_| Status::NoContent).ok_or(Status::NotFound.into())
+}
@@ -0,0 +1,50 @@
+//! Session listing route.
+
+use rocket::{get, serde::json::Json};
+use surrealdb::sql::Duration;
+
+use crate::database::{Database, Take};
+use super::{
+    super::super::{
+        login::{Login, User}, problem::{Context, Error}, Config,
+    },
+    components::SessionOut,
+};
+
+#[utoipa::path(
+    context_path = "/api/auth",
+    responses((
+        status = 200, description = "List of active sessions",
+        body = Vec<SessionOut>,
+    ), (
+        status = 401, description = "Invalid login session",
+    )),
+    security(("login" = [])),
+    tag = "sessions",
+)]
+
+/// GET /api/auth/sessions
+///
+/// List active login sessions of the currently logged in user without
+/// exposing their tokens. The session used for the request is marked as
+/// current, and sessions issued for impersonating the user name the
+/// impersonator.
+#[get("/sessions")]
+pub async fn route(
+    user: Login<User>, db: &Database, config: &Config,
+) -> Result<Json<Vec<SessionOut>>, Error> {
+    let sessions: Vec<SessionOut> = db.query("
+        SELECT
+            id, created, last_used, expires, ip, user_agent, impersonator,
+            id = type::thing('login', $sid) AS current
+        FROM login
+        WHERE user = type::thing('user', $uid) AND expires > time::now()
+            AND last_used > time::now() - $idle
+        ORDER BY last_used DESC
+    ").bind(("uid", &user.id.0)).bind(("sid", &user.session.0))
+        .bind(("idle", Duration::from_secs(config.session_idle_timeout)))
+        .await.take(0)
+        .context("error retrieving sessions")?;
+
+    Ok(Json(sessions))
+}
@@ -0,0 +1,5 @@
+//! Login session routes.
+
+pub mod components;
+pub mod index;
+pub mod destroy;
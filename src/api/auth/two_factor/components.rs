@@ -0,0 +1,44 @@
+//! Two-factor authentication route components.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+/// Two-factor setup output body.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TwoFactorSetupOut {
+    #[schema(example = "JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP")]
+    pub secret: String,
+
+    #[schema(example = "otpauth://totp/Backend:alice@example.com?\
+        secret=JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP&issuer=Backend\
+        &algorithm=SHA1&digits=6&period=30")]
+    pub uri: String,
+}
+
+/// Two-factor code input body.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct TwoFactorCodeIn {
+    #[schema(example = "123456")]
+    #[validate(length(min = 6, max = 10))]
+    pub code: String,
+}
+
+/// Two-factor recovery codes output body.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RecoveryCodesOut {
+    #[schema(example = json!(["k3j8x9p2qa", "m2n7b4v1cz"]))]
+    pub codes: Vec<String>,
+}
+
+/// Second factor verification input body.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct TwoFactorVerifyIn {
+    #[schema(example = "Ct6LXRBOcKKPdJAiiTKYb6NgQJWhxyLL")]
+    #[validate(length(min = 32, max = 32))]
+    pub challenge: String,
+
+    #[schema(example = "123456")]
+    #[validate(length(min = 6, max = 10))]
+    pub code: String,
+}
@@ -0,0 +1,64 @@
+//! Two-factor disable route.
+
+use rocket::{http::Status, post, serde::json::Json};
+use validator::Validate;
+
+use crate::database::{Database, Take};
+use super::{
+    super::super::{
+        audit::Audit, client::Client, login::{Direct, Login},
+        problem::{Context, Error},
+    },
+    components::TwoFactorCodeIn,
+    consume_code,
+};
+
+#[utoipa::path(
+    context_path = "/api/auth",
+    request_body = TwoFactorCodeIn,
+    responses(
+        (status = 204, description = "Two-factor authentication disabled"),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Invalid code or impersonation session"),
+        (status = 422, description = "Invalid code format"),
+    ),
+    security(("login" = [])),
+    tag = "two-factor authentication",
+)]
+
+/// POST /api/auth/2fa/disable
+///
+/// Disable two-factor authentication of the currently logged in user after
+/// verifying a TOTP or recovery code, and delete all recovery codes.
+/// Disabling is recorded in the audit log and not available in impersonation
+/// sessions.
+#[post("/2fa/disable", data = "<data>")]
+pub async fn route(
+    user: Login<Direct>, db: &Database, client: Client,
+    data: Json<TwoFactorCodeIn>,
+) -> Result<Status, Error> {
+    // validate input
+    data.validate()?;
+
+    // verify and consume code
+    let valid = consume_code(db, &user.id.0, &data.code).await
+        .context("error verifying two-factor code")?;
+    if !valid {
+        return Err(Error::new(
+            Status::Forbidden, "invalid_code", "Invalid code",
+        ));
+    }
+
+    // query database to remove secret and recovery codes
+    let audit = Audit::new("auth.2fa_disable", &client).actor(&user.id.0);
+    audit.query(db, "
+        UPDATE type::thing('user', $uid) SET
+            totp_secret = NONE, totp_pending = NONE, totp_step = NONE;
+        DELETE recovery_code WHERE user = type::thing('user', $uid);
+    ").bind(("uid", &user.id.0))
+        .await.check()
+        .context("error executing two-factor disable query")?;
+
+    // return success status
+    Ok(Status::NoContent)
+}
@@ -0,0 +1,98 @@
+//! Two-factor enable route.
+
+use rocket::{http::Status, post, serde::json::Json};
+use validator::Validate;
+
+use crate::{database::{Database, Take}, totp};
+use super::{
+    super::super::{
+        audit::Audit, client::Client, login::{Direct, Login},
+        problem::{Context, Error},
+    },
+    components::{RecoveryCodesOut, TwoFactorCodeIn},
+};
+
+#[utoipa::path(
+    context_path = "/api/auth",
+    request_body = TwoFactorCodeIn,
+    responses(
+        (
+            status = 200, description = "Two-factor authentication enabled",
+            body = RecoveryCodesOut,
+        ),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Invalid code or impersonation session"),
+        (status = 409, description = "No pending setup"),
+        (status = 422, description = "Invalid code format"),
+    ),
+    security(("login" = [])),
+    tag = "two-factor authentication",
+)]
+
+/// POST /api/auth/2fa/enable
+///
+/// Activate pending TOTP secret of the currently logged in user after
+/// verifying a code generated from it. Returns 10 single-use recovery codes,
+/// replacing any previous ones, which are only shown once. Enabling is
+/// recorded in the audit log and not available in impersonation sessions.
+#[post("/2fa/enable", data = "<data>")]
+pub async fn route(
+    user: Login<Direct>, db: &Database, client: Client,
+    data: Json<TwoFactorCodeIn>,
+) -> Result<Json<RecoveryCodesOut>, Error> {
+    // validate input
+    data.validate()?;
+
+    // query database for pending secret
+    let secret: Option<String> = db.query("
+        SELECT VALUE totp_pending FROM ONLY type::thing('user', $uid);
+    ").bind(("uid", &user.id.0))
+        .await.take(0)
+        .context("error executing pending secret query")?;
+
+    // return conflict error if there is no pending setup
+    let secret = secret.ok_or(Error::new(
+        Status::Conflict, "setup_not_pending",
+        "There is no pending two-factor setup",
+    ))?;
+
+    // verify code against pending secret
+    let step = totp::verify(&secret, &data.code, totp::now())
+        .ok_or(Error::new(Status::Forbidden, "invalid_code", "Invalid code"))?;
+
+    // query database to activate secret and store hashed recovery codes
+    let codes = totp::recovery_codes(10);
+    let audit = Audit::new("auth.2fa_enable", &client).actor(&user.id.0);
+    let enabled: Option<bool> = audit.query(db, "
+        let $user = type::thing('user', $uid);
+        let $audited = $user.totp_pending = $secret;
+
+        if $audited {
+            UPDATE $user SET
+                totp_secret = totp_pending, totp_pending = NONE,
+                totp_step = $step;
+
+            DELETE recovery_code WHERE user = $user;
+            for $code in $codes {
+                CREATE recovery_code SET
+                    user = $user, code = crypto::sha256($code);
+            };
+
+            true;
+        };
+    ").bind(("uid", &user.id.0)).bind(("secret", &secret))
+        .bind(("step", step)).bind(("codes", &codes))
+        .await.take(2)
+        .context("error executing two-factor enable query")?;
+
+    // return conflict error if pending secret changed in the meantime
+    if enabled != Some(true) {
+        return Err(Error::new(
+            Status::Conflict, "setup_not_pending",
+            "There is no pending two-factor setup",
+        ));
+    }
+
+    // return recovery codes
+    Ok(Json(RecoveryCodesOut { codes }))
+}
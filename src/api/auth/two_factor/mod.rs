@@ -0,0 +1,62 @@
+//! Two-factor authentication routes.
+
+use surrealdb::{engine::any::Any, Surreal};
+use serde::Deserialize;
+
+use crate::{database::Record, totp};
+
+pub mod components;
+pub mod setup;
+pub mod enable;
+pub mod disable;
+pub mod verify;
+
+/// Database response type for TOTP state.
+#[derive(Deserialize)]
+struct TotpState {
+    totp_secret: Option<String>,
+    totp_step: Option<u64>,
+}
+
+/// Verify TOTP or recovery code of a user and consume it on success. TOTP
+/// codes may only be used once, which is enforced by advancing the last used
+/// time step in a single conditional update, and recovery codes are deleted
+/// when used.
+pub async fn consume_code(
+    db: &Surreal<Any>, uid: &str, code: &str,
+) -> Result<bool, surrealdb::Error> {
+    // fetch TOTP secret and last used time step
+    let state: Option<TotpState> = db.query("
+        SELECT totp_secret, totp_step FROM ONLY type::thing('user', $uid)
+    ").bind(("uid", uid)).await?.take(0)?;
+
+    // check TOTP code and mark time step as used unless already advanced
+    let step = state.as_ref()
+        .and_then(|s| Some((s.totp_secret.as_ref()?, s.totp_step)))
+        .and_then(|(secret, last)| totp::verify(secret, code, totp::now())
+            .filter(|step| last.is_none_or(|last| *step > last)));
+
+    if let Some(step) = step {
+        let updated: Vec<Record> = db.query("
+            UPDATE type::thing('user', $uid) SET totp_step = $step
+            WHERE totp_step IS NONE OR totp_step < $step
+            RETURN AFTER
+        ").bind(("uid", uid)).bind(("step", step)).await?.take(0)?;
+        if !updated.is_empty() { return Ok(true); }
+    }
+
+    // check and delete recovery code
+    let deleted: Option<bool> = db.query("
+        let $deleted = (
+            DELETE recovery_code
+            WHERE user = type::thing('user', $uid)
+                AND code = crypto::sha256($code)
+            RETURN BEFORE
+        );
+
+        array::len($deleted) > 0;
+    ").bind(("uid", uid)).bind(("code", code.to_lowercase()))
+        .await?.take(1)?;
+
+    Ok(deleted.unwrap_or(false))
+}
@@ -0,0 +1,63 @@
+//! Two-factor setup route.
+
+use rocket::{http::Status, post, serde::json::Json};
+
+use crate::{database::{Database, Take}, totp};
+use super::{
+    super::super::{
+        audit::Audit, client::Client, login::{Direct, Login},
+        problem::{Context, Error}, Config,
+    },
+    components::TwoFactorSetupOut,
+};
+
+#[utoipa::path(
+    context_path = "/api/auth",
+    responses(
+        (
+            status = 200, description = "Pending secret created",
+            body = TwoFactorSetupOut,
+        ),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Impersonation session"),
+        (status = 409, description = "Two-factor authentication enabled"),
+    ),
+    security(("login" = [])),
+    tag = "two-factor authentication",
+)]
+
+/// POST /api/auth/2fa/setup
+///
+/// Create new pending TOTP secret for the currently logged in user. The
+/// secret becomes active after confirming it with a valid code. Not available
+/// in impersonation sessions. The setup is recorded in the audit log.
+#[post("/2fa/setup")]
+pub async fn route(
+    user: Login<Direct>, db: &Database, client: Client, config: &Config,
+) -> Result<Json<TwoFactorSetupOut>, Error> {
+    let secret = totp::secret();
+
+    // query database to store pending secret if 2FA is not enabled yet
+    let audit = Audit::new("auth.2fa_setup", &client).actor(&user.id.0);
+    let email: Option<String> = audit.query(db, "
+        let $user = type::thing('user', $uid);
+        let $audited = !$user.totp_secret;
+
+        if $audited {
+            UPDATE $user SET totp_pending = $secret;
+            $user.email;
+        };
+    ").bind(("uid", &user.id.0)).bind(("secret", &secret))
+        .await.take(2)
+        .context("error executing two-factor setup query")?;
+
+    // return conflict error if 2FA is already enabled
+    let email = email.ok_or(Error::new(
+        Status::Conflict, "two_factor_enabled",
+        "Two-factor authentication is already enabled",
+    ))?;
+
+    // return secret and provisioning URI
+    let uri = totp::uri(&secret, &config.totp_issuer, &email);
+    Ok(Json(TwoFactorSetupOut { secret, uri }))
+}
@@ -0,0 +1,134 @@
+//! Second factor verification route.
+
+use rocket::{http::{CookieJar, Status}, post, serde::json::Json};
+use serde::Deserialize;
+use surrealdb::sql::Duration;
+use validator::Validate;
+
+use crate::{database::{Database, Id, Take}, mail::Mail};
+use super::{
+    super::{
+        super::{
+            audit::Audit, client::Client, cookie, problem::{Context, Error},
+            throttle::Throttle, Config,
+        },
+        components::LoginOut,
+    },
+    components::TwoFactorVerifyIn,
+    consume_code,
+};
+
+/// Maximum number of verification attempts per challenge.
+const MAX_ATTEMPTS: u64 = 5;
+
+/// Database response type for challenges.
+#[derive(Deserialize)]
+struct Challenge {
+    user: Id<String>,
+    name: String,
+    email: String,
+    attempts: u64,
+}
+
+#[utoipa::path(
+    context_path = "/api/auth",
+    request_body = TwoFactorVerifyIn,
+    responses(
+        (status = 200, description = "Login successful", body = LoginOut),
+        (status = 401, description = "Invalid challenge or code"),
+        (status = 422, description = "Invalid challenge or code format"),
+        (status = 429, description = "Too many failed login attempts"),
+    ),
+    tag = "two-factor authentication",
+)]
+
+/// POST /api/auth/2fa/verify
+///
+/// Create login session for a login challenge using a TOTP or recovery code.
+/// Challenges are deleted after 5 failed attempts. Codes count as login
+/// attempts for the user's email address and the client IP, which are locked
+/// like failed logins. Created sessions and invalid codes are recorded in the
+/// audit log. With cookie sessions enabled, the session is also set as cookie.
+#[post("/2fa/verify", data = "<data>")]
+pub async fn route(
+    db: &Database, config: &Config, mail: &Mail, client: Client,
+    cookies: &CookieJar<'_>, data: Json<TwoFactorVerifyIn>,
+) -> Result<Json<LoginOut>, Error> {
+    // validate input
+    data.validate()?;
+
+    // query database to count attempt of valid challenge
+    let challenge: Option<Challenge> = db.query("
+        DELETE login_challenge WHERE expires < time::now();
+        UPDATE login_challenge SET attempts += 1
+        WHERE token = crypto::sha256($challenge)
+        RETURN user, user.name AS name, user.email AS email, attempts;
+    ").bind(("challenge", &data.challenge))
+        .await.take(1)
+        .context("error executing challenge query")?;
+
+    // return unauthorized error for unknown or exhausted challenges
+    let invalid = || Error::new(
+        Status::Unauthorized, "invalid_challenge",
+        "Invalid or expired challenge",
+    );
+    let challenge = challenge.ok_or_else(invalid)?;
+    if challenge.attempts > MAX_ATTEMPTS {
+        db.query("
+            DELETE login_challenge WHERE token = crypto::sha256($challenge);
+        ").bind(("challenge", &data.challenge))
+            .await.check()
+            .context("error executing challenge delete query")?;
+        return Err(invalid());
+    }
+
+    // count attempt and reject it while email address or client IP are locked
+    let mut throttle = Throttle::new(&challenge.email, &client);
+    throttle.attempt(db, config).await?;
+
+    // verify and consume code
+    let valid = consume_code(db, &challenge.user.0, &data.code).await
+        .context("error verifying two-factor code")?;
+    if !valid {
+        Audit::new("auth.login_failed", &client).query(db, "
+            let $target = type::thing('user', $uid);
+        ").bind(("uid", &challenge.user.0))
+            .await.check()
+            .context("error recording failed login")?;
+        if throttle.fail(config) {
+            mail.spawn(&challenge.email, "account-locked", &[
+                ("name", &challenge.name),
+            ]);
+        }
+        return Err(Error::new(
+            Status::Unauthorized, "invalid_code", "Invalid code",
+        ));
+    }
+
+    // query database to delete challenge and create login
+    let audit = Audit::new("auth.login", &client).actor(&challenge.user.0);
+    let login: Option<LoginOut> = audit.query(db, "
+        DELETE login_challenge WHERE token = crypto::sha256($challenge);
+
+        let $secret = rand::string();
+        CREATE ONLY login SET
+            user = type::thing('user', $uid), token = crypto::sha256($secret),
+            expires = time::now() + $lifetime,
+            ip = $ip, user_agent = $agent
+        RETURN
+            user AS id, user.name AS name, user.role AS role,
+            $secret AS token, expires;
+    ").bind(("challenge", &data.challenge)).bind(("uid", &challenge.user.0))
+        .bind(("lifetime", Duration::from_secs(config.session_lifetime)))
+        .bind(("ip", &client.ip)).bind(("agent", &client.user_agent))
+        .await.take(2)
+        .context("error executing login query")?;
+
+    // forget failed attempts, set session cookie if enabled, and return login
+    let login = login.ok_or_else(invalid)?;
+    throttle.reset(db, config).await?;
+    if config.cookie_sessions {
+        cookie::set(cookies, &login.token, config.session_lifetime);
+    }
+    Ok(Json(login))
+}
@@ -0,0 +1,34 @@
+//! Client information request guard.
+
+use std::{convert::Infallible, net::IpAddr};
+
+use rocket::{request::{FromRequest, Outcome}, Request};
+
+use crate::config::APIConfig;
+
+/// Client IP address and user agent of the request.
+#[derive(Debug)]
+pub struct Client {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Client {
+    type Error = Infallible;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        // only trust IP header if configured, otherwise use socket address
+        let header = req.rocket().state::<APIConfig>()
+            .and_then(|config| config.ip_header.as_deref());
+        let ip = header
+            .and_then(|name| req.headers().get_one(name))
+            .and_then(|value| value.trim().parse::<IpAddr>().ok())
+            .or_else(|| req.remote().map(|remote| remote.ip()));
+
+        Outcome::Success(Self {
+            ip: ip.map(|ip| ip.to_string()),
+            user_agent: req.headers().get_one("user-agent").map(Into::into),
+        })
+    }
+}
@@ -0,0 +1,68 @@
+//! Cookie-based login sessions for browser frontends with double-submit CSRF
+//! protection.
+//!
+//! The session token is kept in an encrypted `HttpOnly` cookie out of reach of
+//! scripts, next to a readable CSRF cookie derived from it. State-changing
+//! requests authenticated via cookie need to echo the CSRF cookie in the
+//! `X-CSRF-Token` header, which other sites can neither read nor forge.
+
+use rocket::{
+    http::{Cookie, CookieJar, Method, SameSite, Status},
+    time::Duration,
+    Request,
+};
+use sha2::{Digest, Sha256};
+
+/// Name of the private cookie containing the session token.
+pub const SESSION: &str = "session";
+
+/// Name of the cookie containing the CSRF token readable by scripts.
+pub const CSRF: &str = "csrf";
+
+/// Header echoing the CSRF token for state-changing requests.
+pub const HEADER: &str = "X-CSRF-Token";
+
+/// Set session and CSRF cookies for the session token, expiring after the
+/// given number of seconds.
+pub fn set(cookies: &CookieJar<'_>, token: &str, lifetime: u64) {
+    let max_age = Duration::seconds(lifetime.try_into().unwrap_or(i64::MAX));
+    cookies.add_private(Cookie::build((SESSION, token.to_string()))
+        .http_only(true).secure(true).same_site(SameSite::Strict)
+        .max_age(max_age));
+    cookies.add(Cookie::build((CSRF, csrf(token)))
+        .http_only(false).secure(true).same_site(SameSite::Strict)
+        .max_age(max_age));
+}
+
+/// Remove session and CSRF cookies.
+pub fn clear(cookies: &CookieJar<'_>) {
+    cookies.remove_private(SESSION);
+    cookies.remove(CSRF);
+}
+
+/// Get session token from the private cookie, if any. Requests with methods
+/// other than GET, HEAD, and OPTIONS are rejected with forbidden status unless
+/// they carry the matching CSRF token in the header.
+pub fn token(req: &Request<'_>) -> Result<Option<String>, Status> {
+    let Some(cookie) = req.cookies().get_private(SESSION) else {
+        return Ok(None);
+    };
+    let token = cookie.value().to_string();
+
+    // require CSRF token for state-changing requests
+    let safe = matches!(
+        req.method(), Method::Get | Method::Head | Method::Options,
+    );
+    let valid = req.headers().get_one(HEADER) == Some(csrf(&token).as_str());
+    if !safe && !valid { return Err(Status::Forbidden); }
+
+    Ok(Some(token))
+}
+
+/// Derive CSRF token from session token.
+fn csrf(token: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(b"csrf:");
+    hasher.update(token);
+    format!("{:x}", hasher.finalize())
+}
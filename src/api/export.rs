@@ -0,0 +1,114 @@
+//! Registry of tables contributing to personal data exports.
+
+use serde_json::{Map, Value};
+use surrealdb::{engine::any::Any, sql, Surreal};
+
+/// Section of personal data exports selecting data about the user `$user`,
+/// which must not include secrets like password or token hashes.
+#[derive(Debug, Clone)]
+pub struct Section {
+    pub name: &'static str,
+    pub query: &'static str,
+}
+
+/// Registry of export sections, managed by rocket. Applications can manage
+/// their own registry extending the default one with sections of their
+/// tables before the API is mounted.
+#[derive(Debug, Clone)]
+pub struct Registry(Vec<Section>);
+
+impl Default for Registry {
+    /// Create registry with sections of all built-in per-user tables.
+    fn default() -> Self {
+        Self(Vec::new())
+            .register("user", "
+                SELECT * OMIT password, totp_secret, totp_pending, totp_step
+                FROM ONLY $user
+            ")
+            .register("sessions", "
+                SELECT id, created, last_used, expires, ip, user_agent
+                FROM login WHERE user = $user
+            ")
+            .register("api_keys", "
+                SELECT id, name, scopes, created, last_used, expires
+                FROM api_key WHERE user = $user
+            ")
+            .register("login_challenges", "
+                SELECT id, attempts, expires FROM login_challenge
+                WHERE user = $user
+            ")
+            .register("login_attempts", "
+                SELECT failures, last_failure, locked_until
+                FROM ONLY type::thing('login_attempt', 'email:' + $user.email)
+            ")
+            .register("recovery_codes", "
+                SELECT id FROM recovery_code WHERE user = $user
+            ")
+            .register("password_resets", "
+                SELECT id, expires FROM password_reset WHERE user = $user
+            ")
+            .register("email_changes", "
+                SELECT id, email, expires FROM email_change WHERE user = $user
+            ")
+            .register("account_restores", "
+                SELECT id, expires FROM account_restore WHERE user = $user
+            ")
+            .register("memberships", "
+                SELECT organization, organization.name AS name, role, created
+                FROM membership WHERE user = $user
+            ")
+            .register("membership_invitations", "
+                SELECT id, organization, role, expires
+                FROM membership_invitation WHERE email = $user.email
+            ")
+            .register("ownership_transfers", "
+                SELECT id, sender, recipient, keep, expires
+                FROM ownership_transfer
+                WHERE sender = $user OR recipient = $user
+            ")
+            .register("audit_events", "
+                SELECT id, action, actor, target, diff, ip, user_agent, created
+                FROM audit_event WHERE actor = $user OR target = $user
+                ORDER BY created
+            ")
+    }
+}
+
+impl Registry {
+    /// Add section to the registry.
+    pub fn with(mut self, section: Section) -> Self {
+        self.0.push(section);
+        self
+    }
+
+    /// Add section to the registry by its name and query.
+    fn register(self, name: &'static str, query: &'static str) -> Self {
+        self.with(Section { name, query })
+    }
+
+    /// Collect all sections for user by their ID, or none if user not found.
+    pub async fn export(
+        &self, db: &Surreal<Any>, uid: &str,
+    ) -> Result<Option<Value>, surrealdb::Error> {
+        // query all sections at once
+        let mut query = String::from("let $user = type::thing('user', $uid);");
+        for section in &self.0 {
+            query.push_str(section.query);
+            query.push(';');
+        }
+        let mut response = db.query(query).bind(("uid", uid)).await?;
+
+        // assemble document from sections in registration order
+        let mut document = Map::new();
+        for (i, section) in self.0.iter().enumerate() {
+            let value: sql::Value = response.take(i + 1)?;
+            document.insert(section.name.into(), value.into_json());
+        }
+
+        // return nothing if user section is empty
+        match document.get("user") {
+            Some(Value::Null) | None => Ok(None),
+            _ => Ok(Some(Value::Object(document))),
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! Invite code route components.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::database::Id;
+
+/// Invite code creation input body.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct InviteIn {
+    /// Number of registrations the code may be used for, defaults to 1.
+    #[schema(example = 1)]
+    #[validate(range(min = 1))]
+    pub max_uses: Option<u64>,
+
+    /// Seconds until the code expires, defaults to 7 days and is limited to one
+    /// year.
+    #[schema(example = 604800)]
+    #[validate(range(min = 1, max = 31536000))]
+    pub lifetime: Option<u64>,
+}
+
+/// Invite code output body.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct InviteOut {
+    pub id: Id<String>,
+
+    #[schema(example = 0)]
+    pub uses: u64,
+
+    #[schema(example = 1)]
+    pub max_uses: u64,
+
+    #[schema(format = DateTime, example = "2024-04-13T12:25:04Z")]
+    pub created: String,
+
+    #[schema(format = DateTime, example = "2024-04-20T12:25:04Z")]
+    pub expires: String,
+}
+
+/// Created invite code output body.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct InviteCodeOut {
+    pub id: Id<String>,
+
+    #[schema(example = "Ct6LXRBOcKKPdJAiiTKYb6NgQJWhxyLL")]
+    pub code: String,
+
+    #[schema(example = 1)]
+    pub max_uses: u64,
+
+    #[schema(format = DateTime, example = "2024-04-20T12:25:04Z")]
+    pub expires: String,
+}
@@ -0,0 +1,60 @@
+//! Invite code creation route.
+
+use rocket::{http::Status, post, serde::json::Json};
+use surrealdb::sql::Duration;
+use validator::Validate;
+
+use crate::database::{Database, Take};
+use super::{
+    super::{
+        login::{Login, Perm}, permission::InvitesManage,
+        problem::{Context, Error},
+    },
+    components::{InviteCodeOut, InviteIn},
+};
+
+/// Default seconds until invite codes expire.
+const LIFETIME: u64 = 7 * 24 * 60 * 60;
+
+#[utoipa::path(
+    context_path = "/api/invites",
+    request_body = InviteIn,
+    responses(
+        (status = 201, description = "Created invite code", body = InviteCodeOut),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Insufficient privileges"),
+        (status = 422, description = "Invalid invite code data"),
+    ),
+    security(("login" = [])),
+    tag = "invites",
+)]
+
+/// POST /api/invites
+///
+/// Create invite code for registering with the invite registration policy.
+/// The code is only returned once. Requires the `invites:manage` permission.
+#[post("/", data = "<data>")]
+pub async fn route(
+    _user: Login<Perm<InvitesManage>>, db: &Database, data: Json<InviteIn>,
+) -> Result<(Status, Json<InviteCodeOut>), Error> {
+    // validate input
+    data.validate()?;
+
+    // query database to create invite code
+    let lifetime = Duration::from_secs(data.lifetime.unwrap_or(LIFETIME));
+    let invite: Option<InviteCodeOut> = db.query("
+        let $secret = rand::string();
+
+        CREATE ONLY invite_code SET
+            token = crypto::sha256($secret), max_uses = $max_uses,
+            expires = time::now() + $lifetime
+        RETURN id, $secret AS code, max_uses, expires;
+    ").bind(("max_uses", data.max_uses.unwrap_or(1)))
+        .bind(("lifetime", lifetime))
+        .await.take(1)
+        .context("error creating invite code")?;
+
+    // return created invite code
+    let invite = invite.context("created invite code missing")?;
+    Ok((Status::Created, Json(invite)))
+}
@@ -0,0 +1,35 @@
+//! Invite code revocation route.
+
+use rocket::{delete, http::Status};
+
+use crate::database::{Database, Record};
+use super::super::{
+    login::{Login, Perm}, permission::InvitesManage, problem::{Context, Error},
+};
+
+#[utoipa::path(
+    context_path = "/api/invites",
+    responses(
+        (status = 204, description = "Invite code revoked"),
+        (status = 404, description = "Invite code not found"),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Insufficient privileges"),
+    ),
+    security(("login" = [])),
+    tag = "invites",
+)]
+
+/// DELETE /api/invites/{id}
+///
+/// Revoke invite code by its ID. Requires the `invites:manage` permission.
+#[delete("/<id>")]
+pub async fn route(
+    _user: Login<Perm<InvitesManage>>, db: &Database, id: &str,
+) -> Result<Status, Error> {
+    // query database to delete invite code
+    let result: Option<Record> = db.delete(("invite_code", id)).await
+        .context("error revoking invite code")?;
+
+    // return success or not found status
+    result.map(|_| Status::NoContent).ok_or(Status::NotFound.into())
+}
@@ -0,0 +1,45 @@
+//! Invite code listing route.
+
+use rocket::{get, serde::json::Json};
+
+use crate::database::{Database, Take};
+use super::{
+    super::{
+        login::{Login, Perm}, permission::InvitesManage,
+        problem::{Context, Error},
+    },
+    components::InviteOut,
+};
+
+#[utoipa::path(
+    context_path = "/api/invites",
+    responses((
+        status = 200, description = "List of usable invite codes",
+        body = Vec<InviteOut>,
+    ), (
+        status = 401, description = "Invalid login session",
+    ), (
+        status = 403, description = "Insufficient privileges",
+    )),
+    security(("login" = [])),
+    tag = "invites",
+)]
+
+/// GET /api/invites
+///
+/// List unexpired invite codes that have uses left, without the codes
+/// themselves. Requires the `invites:manage` permission.
+#[get("/")]
+pub async fn route(
+    _user: Login<Perm<InvitesManage>>, db: &Database,
+) -> Result<Json<Vec<InviteOut>>, Error> {
+    // query database for usable invite codes
+    let invites: Vec<InviteOut> = db.query("
+        SELECT * FROM invite_code
+        WHERE expires > time::now() AND uses < max_uses
+        ORDER BY created DESC
+    ").await.take(0)
+        .context("error retrieving invite codes")?;
+
+    Ok(Json(invites))
+}
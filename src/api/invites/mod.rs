@@ -0,0 +1,13 @@
+//! Invite code routes for invite-only registration.
+
+use rocket::{routes, Route};
+
+pub mod components;
+pub mod index;
+pub mod create;
+pub mod destroy;
+
+/// Assemble invite code routes.
+pub fn routes() -> Vec<Route> {
+    routes![index::route, create::route, destroy::route]
+}
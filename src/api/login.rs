@@ -1,20 +1,60 @@
-//! User login request guard with role discrimination.
+//! User login request guard with permission discrimination.
 
 use core::fmt;
-use std::marker::PhantomData;
+use std::{collections::HashSet, marker::PhantomData};
 
-use rocket::{error, http::Status, request::{FromRequest, Outcome}, Request};
+use rocket::{http::Status, request::{FromRequest, Outcome}, Request};
 use serde::Deserialize;
-
-use crate::database::{Database, Id};
+use surrealdb::sql::Duration;
+
+use crate::{config::APIConfig, database::{Database, Id, Take}};
+use super::{cookie, permission::Permission};
+
+/// Prefix of API key tokens, distinguishing them from session tokens.
+pub const KEY_PREFIX: &str = "key_";
+
+/// Query validating a session token and marking the session as used.
+const SESSION: &str = "
+    UPDATE login SET last_used = time::now()
+    WHERE token = crypto::sha256($tok) AND expires > time::now()
+        AND last_used > time::now() - $idle
+        AND !user.deleted AND !(user.disabled AND (
+            !user.disabled.until OR user.disabled.until > time::now()
+        ))
+    RETURN
+        user.id AS id, user.name AS name,
+        (type::thing('role', user.role).permissions ?? []) AS permissions,
+        id AS session, impersonator
+";
+
+/// Query validating an API key and marking it as used, limiting the
+/// permissions of the user's role to the scopes of the key.
+const KEY: &str = "
+    UPDATE api_key SET last_used = time::now()
+    WHERE token = crypto::sha256($tok)
+        AND (!expires OR expires > time::now())
+        AND !user.deleted AND !(user.disabled AND (
+            !user.disabled.until OR user.disabled.until > time::now()
+        ))
+    RETURN
+        user.id AS id, user.name AS name,
+        array::intersect(
+            type::thing('role', user.role).permissions ?? [], scopes
+        ) AS permissions,
+        id AS session, scopes
+";
 
 /// Generic login request guard.
 #[derive(Debug, Deserialize)]
 pub struct Login<R: Role> {
     pub id: Id<String>,
     pub name: String,
-    pub role: String,
-    pub token: String,
+    pub permissions: Vec<String>,
+    pub session: Id<String>,
+    pub impersonator: Option<Id<String>>,
+    /// Scopes of the API key used instead of a session, whose ID is then the
+    /// session ID.
+    pub scopes: Option<Vec<String>>,
     #[serde(skip)]
     phantom: PhantomData<R>,
 }
@@ -25,9 +65,38 @@ impl<R: Role> Login<R> {
         R::satisfied(self)
     }
 
-    /// Check if login satisfies specified role.
-    pub fn is<S: Role>(&self, _: S) -> bool {
-        S::satisfied(self)
+    /// Check if login has been granted the named permission.
+    pub fn can(&self, permission: &str) -> bool {
+        self.permissions.iter().any(|granted| granted == permission)
+    }
+
+    /// Convert login into one with the given permissions and requirement,
+    /// such as the permissions granted within an organization, limited to the
+    /// scopes of the API key if any.
+    pub fn scoped<S: Role>(self, mut permissions: Vec<String>) -> Login<S> {
+        if let Some(scopes) = &self.scopes {
+            permissions.retain(|permission| scopes.contains(permission));
+        }
+        Login {
+            id: self.id, name: self.name, permissions, session: self.session,
+            impersonator: self.impersonator, scopes: self.scopes,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Check if login has been granted all of the given permissions and more,
+    /// which is required for managing users with or assigning a role.
+    pub fn outranks(&self, permissions: &[String]) -> bool {
+        let own: HashSet<_> = self.permissions.iter().collect();
+        let other: HashSet<_> = permissions.iter().collect();
+        other.is_subset(&own) && other.len() < own.len()
+    }
+
+    /// Check if login has been granted all of the given permissions, which
+    /// suffices for assigning a role within organizations.
+    pub fn holds(&self, permissions: &[String]) -> bool {
+        let own: HashSet<_> = self.permissions.iter().collect();
+        permissions.iter().all(|permission| own.contains(permission))
     }
 }
 
@@ -37,6 +106,9 @@ pub enum Error {
     #[error("error receiving database from request")]
     DatabaseGuard,
 
+    #[error("error receiving API config from request")]
+    ConfigState,
+
     #[error("SurrealDB error")]
     SurrealDB { #[from] source: surrealdb::Error }
 }
@@ -47,14 +119,27 @@ impl <'r, R: Role> FromRequest<'r> for Login<R> {
     type Error = Error;
 
     async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
-        // extract token from header
+        // get API config from request
+        let config = match req.rocket().state::<APIConfig>() {
+            Some(config) => config,
+            None => return Outcome::Error(
+                (Status::InternalServerError, Error::ConfigState)
+            ),
+        };
+
+        // extract token from header, or from session cookie if enabled
         let token = req.headers()
             .get_one("authorization")
             .and_then(|value| value.split_once(" "))
             .and_then(|kv| match kv { ("apikey", k) => Some(k), _ => None });
 
         let token = match token {
-            Some(token) => token,
+            Some(token) => token.to_string(),
+            None if config.cookie_sessions => match cookie::token(req) {
+                Ok(Some(token)) => token,
+                Ok(None) => return Outcome::Forward(Status::Unauthorized),
+                Err(status) => return Outcome::Forward(status),
+            },
             None => return Outcome::Forward(Status::Unauthorized),
         };
 
@@ -65,22 +150,26 @@ impl <'r, R: Role> FromRequest<'r> for Login<R> {
                 return Outcome::Error((status, Error::DatabaseGuard)),
         };
 
-        // get session from database
-        let result: Result<Option<Login<R>>, surrealdb::Error> = db.query("
-            SELECT user.id AS id, user.name AS name, user.role AS role, token
-            FROM ONLY login WHERE token = $tok LIMIT 1
-        ").bind(("tok", token)).await.and_then(|mut r| r.take(0));
+        // get unexpired session or API key from database and mark it as used
+        let query = match token.starts_with(KEY_PREFIX) {
+            true => KEY,
+            false => SESSION,
+        };
+        let result: Result<Option<Login<R>>, _> = db.query(query)
+            .bind(("tok", token))
+            .bind(("idle", Duration::from_secs(config.session_idle_timeout)))
+            .await.take(0);
 
         // handle database errors and invalid session
         let login = match result {
             Ok(Some(login)) => login,
             Ok(None) => return Outcome::Forward(Status::Unauthorized),
             Err(err) => return Outcome::Error(
-                (Status::InternalServerError, err.into())
+                (Status::InternalServerError, (*err).into())
             ),
         };
 
-        // handle user role
+        // handle required permissions
         match login.satisfied() {
             true => Outcome::Success(login),
             false => Outcome::Forward(Status::Forbidden),
@@ -88,7 +177,7 @@ impl <'r, R: Role> FromRequest<'r> for Login<R> {
     }
 }
 
-/// Trait for evaluating user role membership.
+/// Trait for evaluating login requirements.
 pub trait Role: Sized + fmt::Debug {
     fn satisfied<R: Role>(login: &Login<R>) -> bool;
 }
@@ -102,20 +191,21 @@ impl Role for User {
     }
 }
 
-/// Login role for all admin users.
+/// Login role for users acting as themselves in a session, rejecting sessions
+/// issued for impersonating them as well as API keys.
 #[derive(Debug)]
-pub struct Admin;
-impl Role for Admin {
+pub struct Direct;
+impl Role for Direct {
     fn satisfied<R: Role>(login: &Login<R>) -> bool {
-        login.role == "admin" || login.role == "owner"
+        login.impersonator.is_none() && login.scopes.is_none()
     }
 }
 
-/// Login role for system owner.
+/// Login role for users granted the permission `P`.
 #[derive(Debug)]
-pub struct Owner;
-impl Role for Owner {
+pub struct Perm<P: Permission>(PhantomData<P>);
+impl<P: Permission> Role for Perm<P> {
     fn satisfied<R: Role>(login: &Login<R>) -> bool {
-        login.role == "owner"
+        login.can(P::NAME)
     }
 }
@@ -0,0 +1,93 @@
+//! Organization member request guard with permission discrimination.
+
+use rocket::{http::Status, request::{FromRequest, Outcome}, Request};
+use serde::Deserialize;
+
+use crate::database::{Database, Take};
+use super::login::{Error, Login, Role, User};
+
+/// Mount point of routes taking the organization ID as first path parameter.
+pub const BASE: &str = "/api/organizations";
+
+/// Header naming the current organization for routes mounted elsewhere.
+pub const HEADER: &str = "X-Organization";
+
+/// Organization member request guard, whose login carries the permissions
+/// of the member's role within the current organization.
+#[derive(Debug)]
+pub struct Member<R: Role> {
+    pub login: Login<R>,
+    pub organization: String,
+    pub role: String,
+}
+
+/// Database response type for memberships.
+#[derive(Deserialize)]
+struct Membership {
+    role: String,
+    permissions: Vec<String>,
+}
+
+/// Request guard implementation for organization and membership validation.
+#[rocket::async_trait]
+impl <'r, R: Role> FromRequest<'r> for Member<R> {
+    type Error = Error;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        // validate login session regardless of organization
+        let login = match req.guard::<Login<User>>().await {
+            Outcome::Success(login) => login,
+            Outcome::Forward(status) => return Outcome::Forward(status),
+            Outcome::Error(error) => return Outcome::Error(error),
+        };
+
+        // resolve organization from path or header
+        let organization = match req.route().map(|route| route.uri.base()) {
+            Some(BASE) => req.param::<&str>(0).and_then(Result::ok),
+            _ => req.headers().get_one(HEADER),
+        };
+        let organization = match organization {
+            Some(organization) => organization,
+            None => return Outcome::Forward(Status::BadRequest),
+        };
+
+        // get database from request
+        let db = match req.guard::<&Database>().await {
+            Outcome::Success(db) => db,
+            Outcome::Forward(status) | Outcome::Error((status, _)) =>
+                return Outcome::Error((status, Error::DatabaseGuard)),
+        };
+
+        // get membership of logged in user with permissions of their role
+        let result: Result<Option<Membership>, _> = db.query("
+            SELECT
+                role, (type::thing('role', role).permissions ?? [])
+                    AS permissions
+            FROM ONLY membership
+            WHERE organization = type::thing('organization', $org)
+                AND user = type::thing('user', $uid)
+            LIMIT 1
+        ").bind(("org", organization)).bind(("uid", &login.id.0))
+            .await.take(0);
+
+        // handle database errors and organizations without membership
+        let membership = match result {
+            Ok(Some(membership)) => membership,
+            Ok(None) => return Outcome::Forward(Status::NotFound),
+            Err(err) => return Outcome::Error(
+                (Status::InternalServerError, (*err).into())
+            ),
+        };
+
+        // handle required permissions within organization
+        let member = Member {
+            login: login.scoped(membership.permissions),
+            organization: organization.into(),
+            role: membership.role,
+        };
+        match member.login.satisfied() {
+            true => Outcome::Success(member),
+            false => Outcome::Forward(Status::Forbidden),
+        }
+    }
+}
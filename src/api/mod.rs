@@ -1,20 +1,46 @@
 //! Hierarchy of API routes.
 
-use rocket::{error, fairing::AdHoc};
+use rocket::{error, fairing::AdHoc, warn, State};
 use surrealdb::{engine::any::Any, Surreal};
 
-use crate::config::APIConfig;
+use crate::{config::APIConfig, database::Take};
 
+pub mod problem;
 pub mod pow;
+pub mod client;
+pub mod cookie;
+pub mod page;
+pub mod permission;
 pub mod login;
+pub mod member;
+pub mod throttle;
+pub mod purge;
+pub mod export;
+pub mod audit;
 pub mod auth;
 pub mod users;
+pub mod invites;
+pub mod roles;
+pub mod organizations;
+pub mod ownership;
+
+/// API config type alias for abbreviation in route handlers.
+pub type Config = State<APIConfig>;
 
 /// Mount API routes to the rocket instance.
 pub fn mount(config: APIConfig) -> AdHoc {
     AdHoc::try_on_ignite("API Routes", |rocket| async move {
-        // update owner user
-        if let Some(owner) = config.owner {
+        // get database connection managed by rocket
+        let db = match rocket.state::<Surreal<Any>>() {
+            Some(db) => db,
+            None => {
+                error!("Error getting database");
+                return Err(rocket);
+            }
+        };
+
+        // create or update owner user
+        if let Some(owner) = &config.owner {
             // split value into email and password
             let (email, hash) = match owner.split_once(":") {
                 Some(pair) => pair,
@@ -24,45 +50,82 @@ pub fn mount(config: APIConfig) -> AdHoc {
                 },
             };
 
-            // get database connection managed by rocket
-            let db = match rocket.state::<Surreal<Any>>() {
-                Some(db) => db,
-                None => {
-                    error!("Error getting database");
+            // create or update owner user, or only bootstrap the first owner
+            let bootstrap = config.owner_bootstrap;
+            match update_owner(db, email, hash, bootstrap).await {
+                Ok(true) if !bootstrap => warn!(
+                    "ownership has been transferred but API_OWNER_BOOTSTRAP \
+                    is off, so the owner from API_OWNER was restored"
+                ),
+                Ok(_) => (),
+                Err(err) => {
+                    error!("SurrealDB: {:?}", err);
                     return Err(rocket);
                 }
-            };
+            }
+        }
 
-            // create or update owner user
-            if let Err(err) = update_owner(db, email, hash).await {
+        // load key for signing proof of work challenges
+        let key = match pow::Key::load(db).await {
+            Ok(key) => key,
+            Err(err) => {
                 error!("SurrealDB: {:?}", err);
                 return Err(rocket);
             }
-        }
+        };
+
+        // manage default export registry unless extended by application
+        let rocket = match rocket.state::<export::Registry>() {
+            Some(_) => rocket,
+            None => rocket.manage(export::Registry::default()),
+        };
 
-        // mount API routes
+        // mount API routes and error catchers and manage config
         Ok(rocket
+            .manage(config)
+            .manage(pow::Rates::default())
+            .manage(key)
+            .mount("/api/pow", pow::routes())
             .mount("/api/auth", auth::routes())
             .mount("/api/users", users::routes())
+            .mount("/api/invites", invites::routes())
+            .mount("/api/roles", roles::routes())
+            .mount(member::BASE, organizations::routes())
+            .mount("/api/audit", audit::routes())
+            .mount("/api/ownership", ownership::routes())
+            .register("/api", problem::catchers())
+            .attach(purge::mount())
         )
     })
 }
 
-/// Create or update owner user.
+/// Create or update owner user. When bootstrapping, nothing is changed once
+/// any user holds the owner role, so that ownership can be transferred.
+/// Returns whether ownership has ever been transferred.
 async fn update_owner(
-    db: &Surreal<Any>, email: &str, hash: &str,
-) -> Result<(), surrealdb::Error> {
-    db.query("
-        let $uid = (
-            UPDATE ONLY user SET password = $hash, role = 'owner'
-            WHERE email = $email RETURN id
-        ).id;
+    db: &Surreal<Any>, email: &str, hash: &str, bootstrap: bool,
+) -> Result<bool, Box<surrealdb::Error>> {
+    let transferred: Option<bool> = db.query("
+        let $skip = $bootstrap
+            AND !!(SELECT id FROM user WHERE role = 'owner' LIMIT 1);
+
+        let $uid = if !$skip {
+            (
+                UPDATE ONLY user SET password = $hash, role = 'owner'
+                WHERE email = $email RETURN id
+            ).id
+        };
 
-        if !$uid {
+        if !$skip AND !$uid {
             CREATE user SET
                 name = 'Owner', email = $email,
                 password = $hash, role = 'owner';
         };
-    ").bind(("email", email)).bind(("hash", hash)).await?;
-    Ok(())
+
+        !!(SELECT id FROM audit_event
+            WHERE action = 'ownership.accept' LIMIT 1);
+    ").bind(("email", email)).bind(("hash", hash))
+        .bind(("bootstrap", bootstrap))
+        .await.take(3)?;
+    Ok(transferred.unwrap_or(false))
 }
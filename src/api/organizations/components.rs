@@ -0,0 +1,88 @@
+//! Organization route components.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::database::Id;
+
+/// Organization creation and update input body.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct OrganizationIn {
+    #[schema(example = "Example Inc.")]
+    #[validate(length(min = 2))]
+    pub name: String,
+}
+
+/// Organization output body with the role of the logged in user.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OrganizationOut {
+    pub id: Id<String>,
+
+    #[schema(example = "Example Inc.")]
+    pub name: String,
+
+    #[schema(format = DateTime, example = "2024-04-13T12:25:04Z")]
+    pub created: String,
+
+    #[schema(example = "org_owner")]
+    pub role: String,
+}
+
+/// Organization invitation acceptance input body.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct JoinIn {
+    #[schema(example = "Ct6LXRBOcKKPdJAiiTKYb6NgQJWhxyLL")]
+    #[validate(length(min = 32, max = 32))]
+    pub token: String,
+}
+
+/// Member invitation input body.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct MemberInviteIn {
+    #[schema(example = "alice@example.com")]
+    #[validate(email)]
+    pub email: String,
+
+    /// Role within the organization, defaults to "user".
+    #[schema(example = "user")]
+    pub role: Option<String>,
+}
+
+/// Member update input body.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct MemberIn {
+    #[schema(example = "org_admin")]
+    pub role: String,
+}
+
+/// Member output body.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MemberOut {
+    pub id: Id<String>,
+
+    #[schema(example = "Alice")]
+    pub name: String,
+
+    #[schema(example = "alice@example.com")]
+    pub email: String,
+
+    #[schema(example = "user")]
+    pub role: String,
+
+    #[schema(format = DateTime, example = "2024-04-13T12:25:04Z")]
+    pub joined: String,
+}
+
+/// Member invitation output body.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MemberInvitationOut {
+    #[schema(example = "alice@example.com")]
+    pub email: String,
+
+    #[schema(example = "user")]
+    pub role: String,
+
+    #[schema(format = DateTime, example = "2024-04-20T12:25:04Z")]
+    pub expires: String,
+}
@@ -0,0 +1,62 @@
+//! Organization creation route.
+
+use rocket::{http::Status, post, serde::json::Json};
+use validator::Validate;
+
+use crate::database::{Database, Take};
+use super::{
+    super::{
+        audit::Audit, client::Client, login::{Login, User},
+        problem::{Context, Error},
+    },
+    components::{OrganizationIn, OrganizationOut},
+};
+
+#[utoipa::path(
+    context_path = "/api/organizations",
+    request_body = OrganizationIn,
+    responses(
+        (
+            status = 201, description = "Created organization",
+            body = OrganizationOut,
+        ),
+        (status = 401, description = "Invalid login session"),
+        (status = 422, description = "Invalid organization data"),
+    ),
+    security(("login" = [])),
+    tag = "organizations",
+)]
+
+/// POST /api/organizations
+///
+/// Create organization with the currently logged in user as its first member
+/// with the "org_owner" role, whose permissions only cover managing the
+/// organization and its members. The creation is recorded in the audit log.
+#[post("/", data = "<data>")]
+pub async fn route(
+    user: Login<User>, db: &Database, client: Client,
+    data: Json<OrganizationIn>,
+) -> Result<(Status, Json<OrganizationOut>), Error> {
+    // validate input
+    data.validate()?;
+
+    // query database to create organization and owner membership
+    let audit = Audit::new("organization.create", &client)
+        .actor(&user.id.0);
+    let organization: Option<OrganizationOut> = audit.query(db, "
+        let $org = (CREATE ONLY organization SET name = $name).id;
+
+        CREATE membership SET
+            organization = $org, user = type::thing('user', $uid),
+            role = 'org_owner';
+
+        let $diff = { \"organization\": meta::id($org), \"name\": $name };
+        SELECT id, name, created, 'org_owner' AS role FROM ONLY $org;
+    ").bind(("name", &data.name)).bind(("uid", &user.id.0))
+        .await.take(3)
+        .context("error creating organization")?;
+
+    // return created organization
+    let organization = organization.context("created organization missing")?;
+    Ok((Status::Created, Json(organization)))
+}
@@ -0,0 +1,49 @@
+//! Organization deletion route.
+
+use rocket::{delete, http::Status};
+
+use crate::database::{Database, Take};
+use super::super::{
+    audit::Audit, client::Client, login::Perm, member::Member,
+    permission::OrganizationsManage, problem::{Context, Error},
+};
+
+#[utoipa::path(
+    context_path = "/api/organizations",
+    responses(
+        (status = 204, description = "Organization deleted"),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Insufficient privileges"),
+        (status = 404, description = "Organization not found or not a member"),
+    ),
+    security(("login" = [])),
+    tag = "organizations",
+)]
+
+/// DELETE /api/organizations/{org}
+///
+/// Delete organization by its ID together with its memberships and pending
+/// invitations. Requires the `organizations:manage` permission within the
+/// organization. The deletion is recorded in the audit log.
+#[delete("/<org>")]
+pub async fn route(
+    member: Member<Perm<OrganizationsManage>>, db: &Database, client: Client,
+    org: &str,
+) -> Result<Status, Error> {
+    // delete organization, whose memberships are deleted by event
+    let audit = Audit::new("organization.delete", &client)
+        .actor(&member.login.id.0);
+    audit.query(db, "
+        let $org = type::thing('organization', $oid);
+        let $deleted = (DELETE ONLY $org RETURN BEFORE);
+        let $audited = $deleted != NONE;
+        let $diff = {
+            \"organization\": meta::id($org), \"name\": $deleted.name,
+        };
+    ").bind(("oid", org))
+        .await.check()
+        .context("error deleting organization")?;
+
+    // return success
+    Ok(Status::NoContent)
+}
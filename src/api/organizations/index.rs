@@ -0,0 +1,44 @@
+//! Organization listing route.
+
+use rocket::{get, serde::json::Json};
+
+use crate::database::{Database, Take};
+use super::{
+    super::{login::{Login, User}, problem::{Context, Error}},
+    components::OrganizationOut,
+};
+
+#[utoipa::path(
+    context_path = "/api/organizations",
+    responses((
+        status = 200, description = "List of organizations",
+        body = Vec<OrganizationOut>,
+    ), (
+        status = 401, description = "Invalid login session",
+    )),
+    security(("login" = [])),
+    tag = "organizations",
+)]
+
+/// GET /api/organizations
+///
+/// List organizations the currently logged in user is a member of, together
+/// with their role in each of them.
+#[get("/")]
+pub async fn route(
+    user: Login<User>, db: &Database,
+) -> Result<Json<Vec<OrganizationOut>>, Error> {
+    // query database for organizations of logged in user
+    let organizations: Vec<OrganizationOut> = db.query("
+        SELECT
+            organization AS id, organization.name AS name,
+            organization.created AS created, role
+        FROM membership WHERE user = type::thing('user', $uid)
+        ORDER BY name
+    ").bind(("uid", &user.id.0))
+        .await.take(0)
+        .context("error retrieving organizations")?;
+
+    // return organizations
+    Ok(Json(organizations))
+}
@@ -0,0 +1,77 @@
+//! Organization invitation acceptance route.
+
+use rocket::{http::Status, post, serde::json::Json};
+use validator::Validate;
+
+use crate::database::{Database, Take};
+use super::{
+    super::{login::{Login, User}, problem::{Context, Error}},
+    components::{JoinIn, OrganizationOut},
+};
+
+#[utoipa::path(
+    context_path = "/api/organizations",
+    request_body = JoinIn,
+    responses(
+        (
+            status = 200, description = "Joined organization",
+            body = OrganizationOut,
+        ),
+        (status = 401, description = "Invalid login session"),
+        (status = 404, description = "Invitation token not found"),
+        (status = 422, description = "Invalid token"),
+    ),
+    security(("login" = [])),
+    tag = "organizations",
+)]
+
+/// POST /api/organizations/join
+///
+/// Join organization with an invitation token sent to the email address of
+/// the currently logged in user within the past 7 days.
+#[post("/join", data = "<data>")]
+pub async fn route(
+    user: Login<User>, db: &Database, data: Json<JoinIn>,
+) -> Result<Json<OrganizationOut>, Error> {
+    // validate input
+    data.validate()?;
+
+    // query database to consume invitation and create membership
+    let organization: Option<OrganizationOut> = db.query("
+        DELETE membership_invitation WHERE expires < time::now();
+
+        let $user = type::thing('user', $uid);
+        let $invitation = (
+            DELETE membership_invitation
+            WHERE token = crypto::sha256($tok) AND email = $user.email
+            RETURN BEFORE
+        )[0];
+
+        if $invitation {
+            let $existing = (
+                SELECT id FROM membership
+                WHERE organization = $invitation.organization AND user = $user
+            );
+
+            if !$existing {
+                CREATE membership SET
+                    organization = $invitation.organization, user = $user,
+                    role = $invitation.role;
+            };
+
+            (SELECT
+                organization AS id, organization.name AS name,
+                organization.created AS created, role
+            FROM ONLY membership
+            WHERE organization = $invitation.organization AND user = $user
+            LIMIT 1);
+        };
+    ").bind(("uid", &user.id.0)).bind(("tok", &data.token))
+        .await.take(3)
+        .context("error executing organization join query")?;
+
+    // return joined organization or not found error
+    organization.map(Json).ok_or(Error::new(
+        Status::NotFound, "token_not_found", "Invitation token not found",
+    ))
+}
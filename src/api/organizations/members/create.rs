@@ -0,0 +1,113 @@
+//! Organization member invitation route.
+
+use rocket::{http::Status, post, serde::json::Json};
+use serde::Deserialize;
+use validator::Validate;
+
+use crate::{database::{Database, Take}, mail::Mail};
+use super::{
+    super::{
+        super::{
+            audit::Audit, client::Client, login::Perm, member::Member,
+            permission::UsersCreate, problem::{Context, Error},
+        },
+        components::{MemberInvitationOut, MemberInviteIn},
+    },
+    assignable,
+};
+
+/// Database response type for member invitations.
+#[derive(Deserialize)]
+struct DbOutput {
+    token: String,
+    organization: String,
+    #[serde(flatten)]
+    invitation: MemberInvitationOut,
+}
+
+#[utoipa::path(
+    context_path = "/api/organizations",
+    request_body = MemberInviteIn,
+    responses(
+        (
+            status = 202, description = "Invitation sent",
+            body = MemberInvitationOut,
+        ),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Insufficient privileges"),
+        (status = 404, description = "Organization not found or not a member"),
+        (status = 409, description = "Already a member"),
+        (status = 422, description = "Invalid invitation data"),
+    ),
+    security(("login" = [])),
+    tag = "organizations",
+)]
+
+/// POST /api/organizations/{org}/members
+///
+/// Invite member by email address with the given role, which defaults to
+/// "user". The invitation contains a token that needs to be posted to
+/// `/api/organizations/join` within 7 days by a user logged in with that
+/// email address, replacing previous invitations to the address. Requires the
+/// `users:create` permission within the organization and holding all
+/// permissions of the assigned role. The invitation is recorded in the audit
+/// log.
+#[post("/<org>/members", data = "<data>")]
+pub async fn route(
+    member: Member<Perm<UsersCreate>>, db: &Database, mail: &Mail,
+    client: Client, org: &str, data: Json<MemberInviteIn>,
+) -> Result<(Status, Json<MemberInvitationOut>), Error> {
+    // validate input
+    data.validate()?;
+
+    // restrict assigned role to ones held by the logged in member
+    let role = data.role.as_deref().unwrap_or("user");
+    assignable(&member, db, role).await?;
+
+    // query database to replace invitation unless already a member
+    let audit = Audit::new("organization.member_invite", &client)
+        .actor(&member.login.id.0);
+    let result: Option<DbOutput> = audit.query(db, "
+        let $org = type::thing('organization', $oid);
+
+        DELETE membership_invitation WHERE expires < time::now()
+            OR (organization = $org AND email = string::lowercase($email));
+
+        let $existing = (
+            SELECT id FROM membership
+            WHERE organization = $org AND user.email = string::lowercase($email)
+        );
+
+        let $audited = !$existing;
+        let $diff = {
+            \"organization\": meta::id($org),
+            \"email\": string::lowercase($email), \"role\": $role,
+        };
+
+        let $secret = rand::string();
+
+        if !$existing then (
+            CREATE ONLY membership_invitation SET
+                organization = $org, email = $email, role = $role,
+                token = crypto::sha256($secret),
+                expires = time::now() + 7d
+            RETURN
+                $secret AS token, organization.name AS organization, email,
+                role, expires
+        ) end;
+    ").bind(("oid", org)).bind(("email", &data.email)).bind(("role", role))
+        .await.take(6)
+        .context("error executing member invitation query")?;
+    let result = result.ok_or(Error::new(
+        Status::Conflict, "already_member", "User is already a member",
+    ))?;
+
+    // spawn job for sending invitation email
+    mail.spawn(&result.invitation.email, "invite-member", &[
+        ("name", &member.login.name), ("organization", &result.organization),
+        ("token", &result.token),
+    ]);
+
+    // return invitation without token
+    Ok((Status::Accepted, Json(result.invitation)))
+}
@@ -0,0 +1,87 @@
+//! Organization member removal route.
+
+use rocket::{delete, http::Status};
+
+use crate::database::{Database, Take};
+use super::{
+    super::super::{
+        audit::Audit, client::Client, login::User, member::Member,
+        permission::{OrganizationsManage, Permission, UsersDelete},
+        problem::{Context, Error},
+    },
+    target,
+};
+
+#[utoipa::path(
+    context_path = "/api/organizations",
+    responses(
+        (status = 204, description = "Member removed"),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Insufficient privileges"),
+        (status = 404, description = "Organization or member not found"),
+        (status = 409, description = "Last member able to manage organization"),
+    ),
+    security(("login" = [])),
+    tag = "organizations",
+)]
+
+/// DELETE /api/organizations/{org}/members/{id}
+///
+/// Remove member by their user ID. Requires the `users:delete` permission
+/// within the organization and outranking the member except for leaving the
+/// organization, which is refused for the last member with the
+/// `organizations:manage` permission. The removal is recorded in the audit
+/// log.
+#[delete("/<org>/members/<id>")]
+pub async fn route(
+    member: Member<User>, db: &Database, client: Client, org: &str, id: &str,
+) -> Result<Status, Error> {
+    if member.login.id == id {
+        // keep organization manageable when leaving
+        if member.login.can(OrganizationsManage::NAME) {
+            let managers: Option<u64> = db.query("
+                count(
+                    SELECT id FROM membership
+                    WHERE organization = type::thing('organization', $org)
+                        AND (type::thing('role', role).permissions ?? [])
+                            CONTAINS $permission
+                )
+            ").bind(("org", org))
+                .bind(("permission", OrganizationsManage::NAME))
+                .await.take(0)
+                .context("error counting organization managers")?;
+            if managers.unwrap_or(0) <= 1 {
+                return Err(Error::new(
+                    Status::Conflict, "last_manager",
+                    "Last member able to manage the organization cannot leave",
+                ));
+            }
+        }
+    } else {
+        // check privileges on member to be removed
+        member.login.can(UsersDelete::NAME)
+            .then_some(()).ok_or(Status::Forbidden)?;
+        target(&member, db, id).await?;
+    }
+
+    // query database to delete membership
+    let audit = Audit::new("organization.member_remove", &client)
+        .actor(&member.login.id.0).target(id);
+    audit.query(db, "
+        let $org = type::thing('organization', $oid);
+        let $removed = (
+            DELETE membership
+            WHERE organization = $org AND user = type::thing('user', $uid)
+            RETURN BEFORE
+        );
+        let $audited = !!$removed;
+        let $diff = {
+            \"organization\": meta::id($org), \"role\": $removed[0].role,
+        };
+    ").bind(("oid", org)).bind(("uid", id))
+        .await.check()
+        .context("error removing member")?;
+
+    // return success
+    Ok(Status::NoContent)
+}
@@ -0,0 +1,50 @@
+//! Organization member listing route.
+
+use rocket::{get, serde::json::Json};
+
+use crate::database::Database;
+use super::super::{
+    super::{
+        login::Perm, member::Member, page::{Page, Paginated},
+        permission::UsersList, problem::Error,
+    },
+    components::MemberOut,
+};
+
+#[utoipa::path(
+    context_path = "/api/organizations",
+    params(Page),
+    responses(
+        (
+            status = 200, description = "Page of members",
+            body = PaginatedMemberOut,
+        ),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Insufficient privileges"),
+        (status = 404, description = "Organization not found or not a member"),
+        (status = 422, description = "Invalid query parameters"),
+    ),
+    security(("login" = [])),
+    tag = "organizations",
+)]
+
+/// GET /api/organizations/{org}/members?limit={limit}&offset={offset}
+///
+/// List members of organization page by page, sorted by name. Requires the
+/// `users:list` permission within the organization.
+#[get("/<org>/members")]
+pub async fn route(
+    _member: Member<Perm<UsersList>>, db: &Database, page: Page, org: &str,
+) -> Result<Json<Paginated<MemberOut>>, Error> {
+    // query database for page of members and total count
+    let query = page.query(
+        "user AS id, user.name AS name, user.email AS email, role,
+            created AS joined",
+        "membership WHERE organization = type::thing('organization', $org)",
+        "name",
+    );
+    let members = page.fetch(db.query(query).bind(("org", org))).await?;
+
+    // return page in envelope
+    Ok(Json(members))
+}
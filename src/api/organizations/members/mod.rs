@@ -0,0 +1,50 @@
+//! Organization member routes.
+
+use rocket::http::Status;
+use serde::Deserialize;
+
+use crate::database::{Database, Take};
+use super::super::{
+    login::Role, member::Member, problem::{Context, Error}, roles::permissions,
+};
+
+pub mod index;
+pub mod create;
+pub mod update;
+pub mod destroy;
+
+/// Database response type for members targeted by member actions.
+#[derive(Deserialize)]
+struct Target {
+    permissions: Vec<String>,
+}
+
+/// Fetch member of the current organization by their user ID and check that
+/// the logged in member is someone else and outranks them by the permissions
+/// of their roles within the organization.
+async fn target<R: Role>(
+    member: &Member<R>, db: &Database, id: &str,
+) -> Result<Target, Error> {
+    let target: Option<Target> = db.query("
+        SELECT (type::thing('role', role).permissions ?? []) AS permissions
+        FROM ONLY membership
+        WHERE organization = type::thing('organization', $org)
+            AND user = type::thing('user', $uid)
+        LIMIT 1
+    ").bind(("org", &member.organization)).bind(("uid", id))
+        .await.take(0)
+        .context("error retrieving member")?;
+    let target = target.ok_or(Status::NotFound)?;
+
+    (member.login.id != id && member.login.outranks(&target.permissions))
+        .then_some(target).ok_or(Status::Forbidden.into())
+}
+
+/// Check that role exists and that the logged in member holds all of its
+/// permissions, so that owners can make other members co-owners.
+async fn assignable<R: Role>(
+    member: &Member<R>, db: &Database, role: &str,
+) -> Result<(), Error> {
+    member.login.holds(&permissions(db, role).await?).then_some(())
+        .ok_or(Status::Forbidden.into())
+}
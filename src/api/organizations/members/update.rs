@@ -0,0 +1,78 @@
+//! Organization member update route.
+
+use rocket::{http::Status, patch, serde::json::Json};
+use validator::Validate;
+
+use crate::database::{Database, Take};
+use super::{
+    super::{
+        super::{
+            audit::Audit, client::Client, login::Perm, member::Member,
+            permission::RolesAssign, problem::{Context, Error},
+        },
+        components::{MemberIn, MemberOut},
+    },
+    assignable, target,
+};
+
+#[utoipa::path(
+    context_path = "/api/organizations",
+    request_body = MemberIn,
+    responses(
+        (status = 200, description = "Updated member", body = MemberOut),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Insufficient privileges"),
+        (status = 404, description = "Organization or member not found"),
+        (status = 422, description = "Invalid member data"),
+    ),
+    security(("login" = [])),
+    tag = "organizations",
+)]
+
+/// PATCH /api/organizations/{org}/members/{id}
+///
+/// Change role of member by their user ID. Requires the `roles:assign`
+/// permission within the organization, outranking the member, and holding
+/// all permissions of the assigned role. The change is recorded in the audit
+/// log with the previous and new role.
+#[patch("/<org>/members/<id>", data = "<data>")]
+pub async fn route(
+    member: Member<Perm<RolesAssign>>, db: &Database, client: Client,
+    org: &str, id: &str, data: Json<MemberIn>,
+) -> Result<Json<MemberOut>, Error> {
+    // validate input
+    data.validate()?;
+
+    // check privileges on member and on role to be assigned
+    target(&member, db, id).await?;
+    assignable(&member, db, &data.role).await?;
+
+    // query database to update membership and record changed role
+    let audit = Audit::new("organization.member_update", &client)
+        .actor(&member.login.id.0).target(id);
+    let result: Option<MemberOut> = audit.query(db, "
+        let $org = type::thing('organization', $oid);
+        let $user = type::thing('user', $uid);
+        let $before = (
+            SELECT VALUE role FROM membership
+            WHERE organization = $org AND user = $user
+        )[0];
+        let $audited = $before != NONE;
+        let $diff = {
+            \"organization\": meta::id($org),
+            \"before\": { \"role\": $before },
+            \"after\": { \"role\": $role },
+        };
+
+        UPDATE ONLY membership SET role = $role
+        WHERE organization = $org AND user = $user
+        RETURN
+            user AS id, user.name AS name, user.email AS email, role,
+            created AS joined;
+    ").bind(("oid", org)).bind(("uid", id)).bind(("role", &data.role))
+        .await.take(5)
+        .context("error updating member")?;
+
+    // return updated member
+    result.map(Json).ok_or(Status::NotFound.into())
+}
@@ -0,0 +1,25 @@
+//! Organization routes for multi-tenant workspaces and their members.
+//!
+//! Routes below `/<org>` resolve the current organization from the path and
+//! require membership in it, with the permissions of the member's role.
+
+use rocket::{routes, Route};
+
+pub mod components;
+pub mod index;
+pub mod create;
+pub mod show;
+pub mod update;
+pub mod destroy;
+pub mod join;
+pub mod members;
+
+/// Assemble organization routes.
+pub fn routes() -> Vec<Route> {
+    routes![
+        index::route, create::route, show::route, update::route,
+        destroy::route, join::route,
+        members::index::route, members::create::route,
+        members::update::route, members::destroy::route,
+    ]
+}
@@ -0,0 +1,42 @@
+//! Organization retrieval route.
+
+use rocket::{get, http::Status, serde::json::Json};
+
+use crate::database::{Database, Take};
+use super::{
+    super::{login::User, member::Member, problem::{Context, Error}},
+    components::OrganizationOut,
+};
+
+#[utoipa::path(
+    context_path = "/api/organizations",
+    responses(
+        (
+            status = 200, description = "Organization object",
+            body = OrganizationOut,
+        ),
+        (status = 401, description = "Invalid login session"),
+        (status = 404, description = "Organization not found or not a member"),
+    ),
+    security(("login" = [])),
+    tag = "organizations",
+)]
+
+/// GET /api/organizations/{org}
+///
+/// Retrieve organization by its ID. Requires membership in the organization.
+#[get("/<org>")]
+pub async fn route(
+    member: Member<User>, db: &Database, org: &str,
+) -> Result<Json<OrganizationOut>, Error> {
+    // fetch organization from database
+    let organization: Option<OrganizationOut> = db.query("
+        SELECT id, name, created, $role AS role
+        FROM ONLY type::thing('organization', $org)
+    ").bind(("org", org)).bind(("role", &member.role))
+        .await.take(0)
+        .context("error retrieving organization")?;
+
+    // return organization or not found status
+    organization.map(Json).ok_or(Status::NotFound.into())
+}
@@ -0,0 +1,55 @@
+//! Organization update route.
+
+use rocket::{http::Status, patch, serde::json::Json};
+use validator::Validate;
+
+use crate::database::{Database, Take};
+use super::{
+    super::{
+        login::Perm, member::Member, permission::OrganizationsManage,
+        problem::{Context, Error},
+    },
+    components::{OrganizationIn, OrganizationOut},
+};
+
+#[utoipa::path(
+    context_path = "/api/organizations",
+    request_body = OrganizationIn,
+    responses(
+        (
+            status = 200, description = "Updated organization",
+            body = OrganizationOut,
+        ),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Insufficient privileges"),
+        (status = 404, description = "Organization not found or not a member"),
+        (status = 422, description = "Invalid organization data"),
+    ),
+    security(("login" = [])),
+    tag = "organizations",
+)]
+
+/// PATCH /api/organizations/{org}
+///
+/// Rename organization by its ID. Requires the `organizations:manage`
+/// permission within the organization.
+#[patch("/<org>", data = "<data>")]
+pub async fn route(
+    member: Member<Perm<OrganizationsManage>>, db: &Database, org: &str,
+    data: Json<OrganizationIn>,
+) -> Result<Json<OrganizationOut>, Error> {
+    // validate input
+    data.validate()?;
+
+    // query database to update organization
+    let organization: Option<OrganizationOut> = db.query("
+        UPDATE ONLY type::thing('organization', $org) SET name = $name
+        RETURN id, name, created, $role AS role
+    ").bind(("org", org)).bind(("name", &data.name))
+        .bind(("role", &member.role))
+        .await.take(0)
+        .context("error updating organization")?;
+
+    // return updated organization
+    organization.map(Json).ok_or(Status::NotFound.into())
+}
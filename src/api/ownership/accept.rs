@@ -0,0 +1,85 @@
+//! Ownership transfer acceptance route.
+
+use rocket::{http::Status, post, serde::json::Json};
+use validator::Validate;
+
+use crate::database::{Database, Take};
+use super::{
+    super::{
+        audit::Audit, client::Client, login::{Direct, Login},
+        problem::{Context, Error},
+    },
+    components::AcceptIn,
+};
+
+#[utoipa::path(
+    context_path = "/api/ownership",
+    request_body = AcceptIn,
+    responses(
+        (status = 204, description = "Ownership accepted"),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Impersonation session"),
+        (status = 404, description = "Transfer token not found"),
+        (status = 422, description = "Invalid token"),
+    ),
+    security(("login" = [])),
+    tag = "ownership",
+)]
+
+/// POST /api/ownership/accept
+///
+/// Accept ownership offered to the currently logged in user within the past
+/// 7 days, as long as the sender still has the `ownership:transfer`
+/// permission. Unless the sender keeps ownership, owners become admins while
+/// other roles are left unchanged. The transfer is recorded in the audit log
+/// and not available in impersonation sessions.
+#[post("/accept", data = "<data>")]
+pub async fn route(
+    user: Login<Direct>, db: &Database, client: Client, data: Json<AcceptIn>,
+) -> Result<Status, Error> {
+    // validate input
+    data.validate()?;
+
+    // query database to consume offer and update roles
+    let audit = Audit::new("ownership.accept", &client).target(&user.id.0);
+    let result: Option<bool> = audit.query(db, "
+        DELETE ownership_transfer WHERE expires < time::now();
+
+        let $user = type::thing('user', $uid);
+        let $transfer = (
+            DELETE ownership_transfer
+            WHERE token = crypto::sha256($tok) AND recipient = $user
+            RETURN BEFORE
+        )[0];
+
+        let $audited = !!$transfer AND !$transfer.sender.deleted
+            AND (s'ownership:transfer'
+                IN type::thing('role', $transfer.sender.role).permissions);
+        let $actor = $transfer.sender;
+        let $sender_role = $transfer.sender.role;
+        let $sender_role = if $transfer.keep OR $sender_role != 'owner' {
+            $sender_role
+        } else { 'admin' };
+        let $diff = {
+            before: { role: $user.role, sender_role: $transfer.sender.role },
+            after: { role: 'owner', sender_role: $sender_role },
+        };
+
+        if $audited {
+            UPDATE $user SET role = 'owner';
+            UPDATE $transfer.sender SET role = $sender_role;
+        };
+
+        $audited;
+    ").bind(("uid", &user.id.0)).bind(("tok", &data.token))
+        .await.take(9)
+        .context("error executing ownership acceptance query")?;
+
+    // return success or not found error
+    match result.unwrap_or(false) {
+        true => Ok(Status::NoContent),
+        false => Err(Error::new(
+            Status::NotFound, "token_not_found", "Transfer token not found",
+        )),
+    }
+}
@@ -0,0 +1,44 @@
+//! Ownership route components.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::database::Id;
+
+/// Ownership transfer input body.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct TransferIn {
+    #[schema(example = "bob@example.com")]
+    #[validate(email)]
+    pub email: String,
+
+    /// Keep ownership and add the recipient as co-owner instead of handing
+    /// ownership over.
+    #[schema(example = false)]
+    #[serde(default)]
+    pub keep: bool,
+}
+
+/// Ownership transfer output body.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TransferOut {
+    pub id: Id<String>,
+
+    #[schema(example = "bob@example.com")]
+    pub email: String,
+
+    #[schema(example = false)]
+    pub keep: bool,
+
+    #[schema(format = DateTime, example = "2024-04-20T12:25:04Z")]
+    pub expires: String,
+}
+
+/// Ownership transfer acceptance input body.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct AcceptIn {
+    #[schema(example = "Ct6LXRBOcKKPdJAiiTKYb6NgQJWhxyLL")]
+    #[validate(length(min = 32, max = 32))]
+    pub token: String,
+}
@@ -0,0 +1,13 @@
+//! Ownership routes for handing over ownership or adding co-owners, with
+//! confirmation by the recipient.
+
+use rocket::{routes, Route};
+
+pub mod components;
+pub mod transfer;
+pub mod accept;
+
+/// Assemble ownership routes.
+pub fn routes() -> Vec<Route> {
+    routes![transfer::route, accept::route]
+}
@@ -0,0 +1,109 @@
+//! Ownership transfer route.
+
+use rocket::{http::Status, post, serde::json::Json};
+use serde::Deserialize;
+use validator::Validate;
+
+use crate::{database::{Database, Take}, mail::Mail};
+use super::{
+    super::{
+        audit::Audit, client::Client, login::{Login, Perm},
+        permission::OwnershipTransfer, problem::{Context, Error},
+    },
+    components::{TransferIn, TransferOut},
+};
+
+/// Database response type for ownership transfers.
+#[derive(Deserialize)]
+struct DbOutput {
+    token: String,
+    #[serde(flatten)]
+    transfer: TransferOut,
+}
+
+#[utoipa::path(
+    context_path = "/api/ownership",
+    request_body = TransferIn,
+    responses(
+        (status = 202, description = "Transfer offered", body = TransferOut),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Insufficient privileges"),
+        (status = 404, description = "Recipient not found"),
+        (status = 409, description = "Recipient already owner"),
+        (status = 422, description = "Invalid transfer data"),
+    ),
+    security(("login" = [])),
+    tag = "ownership",
+)]
+
+/// POST /api/ownership
+///
+/// Offer ownership to a registered user by their email address, either
+/// handing it over or keeping it and adding them as co-owner. The offer
+/// contains a token that needs to be posted to `/api/ownership/accept`
+/// within 7 days by the recipient, replacing previous offers to them. Once
+/// accepted, owners handing over ownership become admins. Requires the
+/// `ownership:transfer` permission and is recorded in the audit log.
+#[post("/", data = "<data>")]
+pub async fn route(
+    user: Login<Perm<OwnershipTransfer>>, db: &Database, mail: &Mail,
+    client: Client, data: Json<TransferIn>,
+) -> Result<(Status, Json<TransferOut>), Error> {
+    // validate input
+    data.validate()?;
+
+    // query database for role of recipient
+    let role: Option<String> = db.query("
+        SELECT VALUE role FROM ONLY user
+        WHERE email = string::lowercase($email) AND !deleted
+        LIMIT 1
+    ").bind(("email", &data.email))
+        .await.take(0)
+        .context("error retrieving recipient")?;
+    match role.as_deref() {
+        None => return Err(Error::new(
+            Status::NotFound, "user_not_found", "Recipient not found",
+        )),
+        Some("owner") => return Err(Error::new(
+            Status::Conflict, "already_owner", "Recipient is already an owner",
+        )),
+        _ => (),
+    }
+
+    // query database to replace offer to recipient
+    let audit = Audit::new("ownership.offer", &client).actor(&user.id.0);
+    let result: Option<DbOutput> = audit.query(db, "
+        let $sender = type::thing('user', $uid);
+        let $target = (
+            SELECT VALUE id FROM ONLY user
+            WHERE email = string::lowercase($email) LIMIT 1
+        );
+        let $diff = { after: { keep: $keep } };
+
+        DELETE ownership_transfer WHERE expires < time::now()
+            OR (sender = $sender AND recipient = $target);
+
+        let $secret = rand::string();
+
+        CREATE ONLY ownership_transfer SET
+            sender = $sender, recipient = $target, keep = $keep,
+            token = crypto::sha256($secret), expires = time::now() + 7d
+        RETURN id, recipient.email AS email, keep, expires, $secret AS token;
+    ").bind(("uid", &user.id.0)).bind(("email", &data.email))
+        .bind(("keep", data.keep))
+        .await.take(5)
+        .context("error executing ownership transfer query")?;
+    let result = result.context("created ownership transfer missing")?;
+
+    // spawn job for sending offer email
+    let offer = match data.keep {
+        true => "a co-owner",
+        false => "the owner in their place",
+    };
+    mail.spawn(&result.transfer.email, "transfer-ownership", &[
+        ("name", &user.name), ("offer", offer), ("token", &result.token),
+    ]);
+
+    // return offer without token
+    Ok((Status::Accepted, Json(result.transfer)))
+}
@@ -0,0 +1,135 @@
+//! Pagination query guard and response envelope.
+
+use std::convert::Infallible;
+
+use rocket::{
+    http::Status, request::{FromRequest, Outcome}, FromFormField, Request,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::json;
+use surrealdb::{engine::any::Any, method::Query};
+use utoipa::{
+    openapi::{
+        schema::{KnownFormat, SchemaFormat}, ArrayBuilder, ObjectBuilder, Ref,
+        Schema, SchemaType,
+    },
+    IntoParams, ToSchema,
+};
+
+use super::problem::{Context, Error};
+
+/// Default number of items per page.
+const DEFAULT_LIMIT: u32 = 50;
+
+/// Maximum number of items per page.
+const MAX_LIMIT: u32 = 100;
+
+/// Pagination query guard with limit and offset.
+#[derive(Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct Page {
+    /// Maximum number of items to return.
+    #[param(default = 50, minimum = 1, maximum = 100)]
+    pub limit: u32,
+
+    /// Number of items to skip.
+    #[param(default = 0)]
+    pub offset: u32,
+}
+
+/// Request guard implementation parsing the pagination query parameters.
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Page {
+    type Error = Infallible;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let limit = req.query_value::<u32>("limit")
+            .unwrap_or(Ok(DEFAULT_LIMIT));
+        let offset = req.query_value::<u32>("offset").unwrap_or(Ok(0));
+
+        match (limit, offset) {
+            (Ok(limit @ 1..=MAX_LIMIT), Ok(offset)) =>
+                Outcome::Success(Self { limit, offset }),
+            _ => Outcome::Forward(Status::UnprocessableEntity),
+        }
+    }
+}
+
+impl Page {
+    /// Wrap items of this page and total number of items in an envelope.
+    pub fn paginate<T>(&self, items: Vec<T>, total: u64) -> Paginated<T> {
+        Paginated { items, total, limit: self.limit, offset: self.offset }
+    }
+
+    /// Assemble statements selecting fields of this page of records from the
+    /// source, which may include a `WHERE` clause, and counting all of them.
+    pub fn query(&self, fields: &str, source: &str, order: &str) -> String {
+        format!("
+            SELECT {fields} FROM {source}
+                ORDER BY {order} LIMIT $limit START $offset;
+            SELECT count() FROM {source} GROUP ALL;
+        ")
+    }
+
+    /// Execute query assembled by [`Page::query`] with its other parameters
+    /// already bound and wrap its results in an envelope.
+    pub async fn fetch<T: DeserializeOwned>(
+        &self, query: Query<'_, Any>,
+    ) -> Result<Paginated<T>, Error> {
+        let mut response = query
+            .bind(("limit", self.limit)).bind(("offset", self.offset))
+            .await.context("error executing page query")?;
+        let items: Vec<T> = response.take(0)
+            .context("error retrieving page items")?;
+        let total: Option<u64> = response.take((1, "count"))
+            .context("error counting page items")?;
+        Ok(self.paginate(items, total.unwrap_or(0)))
+    }
+}
+
+/// Sort order query parameter.
+#[derive(Debug, Default, Clone, Copy, FromFormField, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Order {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl Order {
+    /// Get SurrealQL keyword of the order.
+    pub fn keyword(self) -> &'static str {
+        match self {
+            Self::Asc => "ASC",
+            Self::Desc => "DESC",
+        }
+    }
+}
+
+/// Paginated response envelope.
+#[derive(Debug, Serialize)]
+pub struct Paginated<T> {
+    pub items: Vec<T>,
+    pub total: u64,
+    pub limit: u32,
+    pub offset: u32,
+}
+
+/// OpenAPI schema of the paginated response envelope for items of the named
+/// schema.
+pub fn schema(items: &str) -> Schema {
+    let integer = |format, example| ObjectBuilder::new()
+        .schema_type(SchemaType::Integer)
+        .format(Some(SchemaFormat::KnownFormat(format)))
+        .minimum(Some(0.0)).example(Some(json!(example)));
+    ObjectBuilder::new()
+        .description(Some("Paginated response envelope."))
+        .property("items", ArrayBuilder::new()
+            .items(Ref::from_schema_name(items)))
+        .property("total", integer(KnownFormat::Int64, 1))
+        .property("limit", integer(KnownFormat::Int32, 50))
+        .property("offset", integer(KnownFormat::Int32, 0))
+        .required("items").required("total").required("limit")
+        .required("offset")
+        .into()
+}
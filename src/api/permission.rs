@@ -0,0 +1,70 @@
+//! Named permissions granted to users by their role, either globally or
+//! within an organization by the role of their membership.
+
+use core::fmt;
+
+use validator::ValidationError;
+
+/// Trait for permission marker types used with the `Perm` login role.
+pub trait Permission: fmt::Debug {
+    /// Name of the permission as stored in the role table.
+    const NAME: &'static str;
+}
+
+/// Define permission marker types and the list of all permission names.
+macro_rules! permissions {
+    ($($(#[$attr:meta])* $name:ident = $value:literal,)*) => {
+        $(
+            $(#[$attr])*
+            #[derive(Debug)]
+            pub struct $name;
+            impl Permission for $name {
+                const NAME: &'static str = $value;
+            }
+        )*
+
+        /// Names of all known permissions.
+        pub const PERMISSIONS: &[&str] = &[$($value),*];
+    };
+}
+
+permissions! {
+    /// List all users, or all members within organizations.
+    UsersList = "users:list",
+    /// Retrieve other users and their sessions.
+    UsersRead = "users:read",
+    /// Create users and send invitations, or invite members.
+    UsersCreate = "users:create",
+    /// Update other users.
+    UsersUpdate = "users:update",
+    /// Delete other users, or remove other members.
+    UsersDelete = "users:delete",
+    /// Suspend and reactivate users.
+    UsersSuspend = "users:suspend",
+    /// Export personal data of other users.
+    UsersExport = "users:export",
+    /// Log in as other users for support purposes.
+    UsersImpersonate = "users:impersonate",
+    /// Reset two-factor authentication of other users.
+    TwoFactorReset = "users:2fa_reset",
+    /// Revoke sessions of other users.
+    SessionsRevoke = "sessions:revoke",
+    /// Create, list, and revoke invite codes.
+    InvitesManage = "invites:manage",
+    /// Assign roles to users, or to members within organizations.
+    RolesAssign = "roles:assign",
+    /// Create, update, and delete custom roles.
+    RolesManage = "roles:manage",
+    /// Rename and delete organizations.
+    OrganizationsManage = "organizations:manage",
+    /// Read the audit log.
+    AuditRead = "audit:read",
+    /// Transfer ownership or add co-owners.
+    OwnershipTransfer = "ownership:transfer",
+}
+
+/// Validate permissions being known permission names.
+pub fn validate(value: &[String]) -> Result<(), ValidationError> {
+    value.iter().all(|permission| PERMISSIONS.contains(&permission.as_str()))
+        .then_some(()).ok_or(ValidationError::new("invalid_permission"))
+}
@@ -0,0 +1,58 @@
+//! Proof of work challenge route.
+
+use rocket::{
+    get, http::Status, serde::json::Json,
+    time::format_description::well_known::Rfc3339, State,
+};
+
+use super::{
+    super::{problem::Error, Config},
+    components::PowChallengeOut, difficulty, Key, Rates,
+};
+
+#[utoipa::path(
+    context_path = "/api/pow",
+    params(
+        (
+            "scope" = String, Query,
+            description = "Scope of the challenge",
+            example = "register",
+        ),
+    ),
+    responses(
+        (status = 200, description = "Challenge issued", body = PowChallengeOut),
+        (status = 404, description = "Unknown scope"),
+    ),
+    tag = "proof of work",
+)]
+
+/// GET /api/pow/challenge?scope={scope}
+///
+/// Issue random challenge for request bodies requiring a proof of work.
+/// Such a body needs to contain the challenge in a `challenge` field and
+/// needs to be chosen such that the binary representation of its SHA512 hash
+/// begins with `difficulty` zeros, e.g. by incrementing a `nonce` field.
+/// Challenges expire after 5 minutes and are spent when used once, so a
+/// solved request body can neither be precomputed nor replayed. They are
+/// signed instead of stored, so issuing them costs no database writes.
+///
+/// Challenges can only be used for routes of the requested scope, which are
+/// `register` and `password_reset`. The difficulty is configured per scope
+/// and may be raised automatically while the scope receives many requests.
+#[get("/challenge?<scope>")]
+pub async fn route(
+    config: &Config, rates: &State<Rates>, key: &State<Key>, scope: &str,
+) -> Result<Json<PowChallengeOut>, Error> {
+    // calculate current difficulty of scope
+    let difficulty = difficulty(config, rates, scope).ok_or(Error::new(
+        Status::NotFound, "unknown_scope", "Unknown proof of work scope",
+    ))?;
+
+    // issue signed challenge
+    let (challenge, issued) = key.issue(scope, difficulty);
+    let expires = issued.expires.format(&Rfc3339)
+        .map_err(|_| Status::InternalServerError)?;
+
+    // return challenge and difficulty
+    Ok(Json(PowChallengeOut { challenge, difficulty, expires }))
+}
@@ -0,0 +1,17 @@
+//! Proof of work route components.
+
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Proof of work challenge output body.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PowChallengeOut {
+    #[schema(example = "Ct6LXRBOcKKPdJAiiTKYb6NgQJWhxyLL")]
+    pub challenge: String,
+
+    #[schema(example = 16)]
+    pub difficulty: u32,
+
+    #[schema(format = DateTime, example = "2024-04-13T12:30:04Z")]
+    pub expires: String,
+}
@@ -0,0 +1,86 @@
+//! Signing of stateless proof of work challenges.
+
+use hmac::{Hmac, Mac};
+use rand::{distributions::Alphanumeric, Rng};
+use rocket::time::{Duration, OffsetDateTime};
+use sha2::Sha256;
+use surrealdb::{engine::any::Any, Surreal};
+
+/// Lifetime of issued challenges.
+const LIFETIME: Duration = Duration::minutes(5);
+
+/// Challenge fields protected by its signature.
+#[derive(Debug)]
+pub struct Challenge {
+    pub nonce: String,
+    pub expires: OffsetDateTime,
+    pub difficulty: u32,
+}
+
+/// Random key for signing challenges, shared by all server instances via the
+/// database.
+pub struct Key(Vec<u8>);
+
+impl Key {
+    /// Load key from database, creating it on the first launch.
+    pub async fn load(db: &Surreal<Any>) -> Result<Self, surrealdb::Error> {
+        let key: Option<String> = db.query("
+            UPDATE secret:pow SET value = value ?? rand::string(64)
+            RETURN VALUE value;
+        ").await?.take(0)?;
+        Ok(Self(key.unwrap_or_default().into_bytes()))
+    }
+
+    /// Issue signed challenge of scope with difficulty.
+    pub fn issue(&self, scope: &str, difficulty: u32) -> (String, Challenge) {
+        let challenge = Challenge {
+            nonce: rand::thread_rng().sample_iter(Alphanumeric).take(32)
+                .map(char::from).collect(),
+            expires: OffsetDateTime::now_utc() + LIFETIME,
+            difficulty,
+        };
+        let payload = payload(&challenge);
+        let tag = self.mac(scope, &payload).finalize().into_bytes();
+        (format!("{payload}.{tag:x}"), challenge)
+    }
+
+    /// Verify signature and expiry of challenge of scope and return it.
+    pub fn verify(&self, scope: &str, value: &str) -> Option<Challenge> {
+        // split challenge into fields and signature
+        let (payload, tag) = value.rsplit_once('.')?;
+        let mut fields = payload.split('.');
+        let (nonce, expires, difficulty) =
+            (fields.next()?, fields.next()?, fields.next()?);
+        if fields.next().is_some() { return None; }
+
+        // check signature in constant time
+        let tag: Vec<u8> = (0..tag.len()).step_by(2)
+            .map(|i| u8::from_str_radix(tag.get(i..i + 2)?, 16).ok())
+            .collect::<Option<_>>()?;
+        self.mac(scope, payload).verify_slice(&tag).ok()?;
+
+        // parse fields and check expiry
+        let expires =
+            OffsetDateTime::from_unix_timestamp(expires.parse().ok()?).ok()?;
+        let challenge = Challenge {
+            nonce: nonce.into(), expires, difficulty: difficulty.parse().ok()?,
+        };
+        (challenge.expires > OffsetDateTime::now_utc()).then_some(challenge)
+    }
+
+    /// Create MAC of payload bound to scope.
+    fn mac(&self, scope: &str, payload: &str) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.0)
+            .expect("HMAC accepts keys of any length");
+        mac.update(scope.as_bytes());
+        mac.update(b".");
+        mac.update(payload.as_bytes());
+        mac
+    }
+}
+
+/// Assemble signed payload of challenge.
+fn payload(challenge: &Challenge) -> String {
+    let Challenge { nonce, expires, difficulty } = challenge;
+    format!("{nonce}.{}.{difficulty}", expires.unix_timestamp())
+}
@@ -0,0 +1,203 @@
+//! Proof of work request body guard and challenge routes.
+
+use std::{marker::PhantomData, ops::Deref};
+
+use rocket::{
+    data::{FromData, Outcome, ToByteUnit}, http::Status, request, routes,
+    serde::json::{self, Json}, Data, Request, Route,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use sha2::{Sha512, Digest};
+use surrealdb::{engine::any::Any, Surreal};
+
+use crate::{config::APIConfig, database::{Database, Record}};
+
+pub mod scope;
+pub mod rate;
+pub mod key;
+pub mod components;
+pub mod challenge;
+
+pub use scope::{PasswordReset, Register, Scope};
+pub use rate::Rates;
+pub use key::Key;
+
+/// Proof of work server error enum.
+#[derive(Debug, thiserror::Error)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum Error {
+    #[error("request body is too long")]
+    TooLong,
+
+    #[error("error reading request body")]
+    Io { #[from] source: std::io::Error },
+
+    #[error("invalid proof of work")]
+    POW,
+
+    #[error("invalid, expired, or spent challenge")]
+    Challenge,
+
+    #[error("error receiving database from request")]
+    DatabaseGuard,
+
+    #[error("error receiving API config from request")]
+    ConfigState,
+
+    #[error("SurrealDB error")]
+    SurrealDB { #[from] source: surrealdb::Error },
+
+    #[error("invalid JSON")]
+    Json { #[from] source: json::serde_json::Error },
+}
+
+/// Request body with proof of work on a server-issued challenge of scope `S`.
+#[derive(Clone, Debug)]
+#[allow(clippy::upper_case_acronyms)]
+pub struct POW<T, S: Scope>(pub T, PhantomData<S>);
+
+/// Challenge field of request bodies.
+#[derive(Deserialize)]
+struct ChallengeField {
+    challenge: String,
+}
+
+impl<T, S: Scope> Deref for POW<T, S> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T: DeserializeOwned, S: Scope> FromData<'r> for POW<Json<T>, S> {
+    type Error = Error;
+
+    async fn from_data(
+        req: &'r Request<'_>, data: Data<'r>,
+    ) -> Outcome<'r, Self> {
+        // get API config, request rates, and signing key from request
+        let (config, rates, key) = match (
+            req.rocket().state::<APIConfig>(), req.rocket().state::<Rates>(),
+            req.rocket().state::<Key>(),
+        ) {
+            (Some(config), Some(rates), Some(key)) => (config, rates, key),
+            _ => return Outcome::Error(
+                (Status::InternalServerError, Error::ConfigState)
+            ),
+        };
+
+        // count request for adaptive difficulty
+        rates.hit(S::NAME);
+
+        // read request body and handle too long ones
+        let body = match data.open(config.pow_body_limit.bytes())
+            .into_bytes().await
+        {
+            Ok(body) if body.is_complete() => body.into_inner(),
+            Ok(_) => return Outcome::Error(
+                (Status::PayloadTooLarge, Error::TooLong)
+            ),
+            Err(err) => return Outcome::Error(
+                (Status::BadRequest, err.into())
+            ),
+        };
+
+        // check proof of work against base difficulty before hitting database
+        if !check(&body, S::difficulty(config)) {
+            return Outcome::Error((Status::PaymentRequired, Error::POW));
+        }
+
+        // extract challenge from request body and verify its signature
+        let challenge = json::from_slice::<ChallengeField>(&body).ok()
+            .and_then(|field| key.verify(S::NAME, &field.challenge));
+        let challenge = match challenge {
+            Some(challenge) => challenge,
+            None => return Outcome::Error(
+                (Status::PaymentRequired, Error::Challenge)
+            ),
+        };
+
+        // check difficulty the challenge was issued with
+        if !check(&body, challenge.difficulty) {
+            return Outcome::Error((Status::PaymentRequired, Error::POW));
+        }
+
+        // get database from request
+        let db = match req.guard::<&Database>().await {
+            request::Outcome::Success(db) => db,
+            request::Outcome::Forward(status)
+            | request::Outcome::Error((status, _)) =>
+                return Outcome::Error((status, Error::DatabaseGuard)),
+        };
+
+        // mark challenge as spent
+        match spend(db, &challenge).await {
+            Ok(true) => (),
+            Ok(false) => return Outcome::Error(
+                (Status::PaymentRequired, Error::Challenge)
+            ),
+            Err(err) => return Outcome::Error(
+                (Status::InternalServerError, err.into())
+            ),
+        }
+
+        // deserialize request body
+        match json::from_slice(&body) {
+            Ok(value) => Outcome::Success(Self(Json(value), PhantomData)),
+            Err(err) if err.is_data() => Outcome::Error(
+                (Status::UnprocessableEntity, err.into())
+            ),
+            Err(err) => Outcome::Error((Status::BadRequest, err.into())),
+        }
+    }
+}
+
+/// Assemble proof of work routes.
+pub fn routes() -> Vec<Route> {
+    routes![challenge::route]
+}
+
+/// Calculate current difficulty of scope by adding the adaptive increase to
+/// its base difficulty.
+pub fn difficulty(
+    config: &APIConfig, rates: &Rates, scope: &str,
+) -> Option<u32> {
+    let base = scope::difficulty(config, scope)?;
+    if !config.pow_adaptive { return Some(base); }
+
+    let increase = rates.increase(scope, config.pow_adaptive_rate);
+    Some((base + increase).min(config.pow_max_difficulty.max(base)))
+}
+
+/// Store challenge as spent until it expires, and return whether it has not
+/// been spent before.
+async fn spend(
+    db: &Surreal<Any>, challenge: &key::Challenge,
+) -> Result<bool, surrealdb::Error> {
+    let spent: Option<Record> = db.query("
+        UPDATE type::thing('pow_challenge', $nonce)
+        SET expires = time::from::secs($expires)
+        RETURN BEFORE
+    ").bind(("nonce", &challenge.nonce))
+        .bind(("expires", challenge.expires.unix_timestamp()))
+        .await?.take(0)?;
+    Ok(spent.is_none())
+}
+
+/// Check whether SHA512 hash of bytes begins with `difficulty` zero bits.
+fn check(data: &[u8], difficulty: u32) -> bool {
+    // calculate SHA512 hash
+    let mut hasher = Sha512::new();
+    hasher.update(data);
+    let hash = hasher.finalize();
+
+    // count leading zero bits
+    let mut zeros = 0;
+    for byte in hash {
+        zeros += byte.leading_zeros();
+        if byte != 0 { break; }
+    }
+    zeros >= difficulty
+}
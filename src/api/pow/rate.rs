@@ -0,0 +1,74 @@
+//! Request rate tracking for adaptive proof of work difficulty.
+
+use std::{
+    collections::HashMap, sync::Mutex, time::{Duration, Instant},
+};
+
+/// Length of the window requests are counted in.
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Request counters of the current and previous window.
+#[derive(Debug)]
+struct Counter {
+    start: Instant,
+    current: u64,
+    previous: u64,
+}
+
+impl Counter {
+    /// Move window forward to include the current time.
+    fn advance(&mut self, now: Instant) {
+        let elapsed = now.duration_since(self.start);
+        if elapsed >= WINDOW * 2 {
+            (self.start, self.current, self.previous) = (now, 0, 0);
+        } else if elapsed >= WINDOW {
+            self.start += WINDOW;
+            self.previous = self.current;
+            self.current = 0;
+        }
+    }
+
+    /// Estimate number of requests within the last window by weighting the
+    /// previous window with its share of the sliding window.
+    fn rate(&self, now: Instant) -> u64 {
+        let elapsed = now.duration_since(self.start).as_secs_f64();
+        let weight = 1.0 - (elapsed / WINDOW.as_secs_f64()).min(1.0);
+        self.current + (self.previous as f64 * weight) as u64
+    }
+}
+
+/// Proof of work protected request rates per scope.
+#[derive(Debug, Default)]
+pub struct Rates(Mutex<HashMap<&'static str, Counter>>);
+
+impl Rates {
+    /// Count request to scope.
+    pub fn hit(&self, scope: &'static str) {
+        let now = Instant::now();
+        let mut counters = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let counter = counters.entry(scope).or_insert(Counter {
+            start: now, current: 0, previous: 0,
+        });
+        counter.advance(now);
+        counter.current += 1;
+    }
+
+    /// Get requests to scope within the last minute.
+    pub fn rate(&self, scope: &str) -> u64 {
+        let now = Instant::now();
+        let mut counters = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        counters.get_mut(scope).map_or(0, |counter| {
+            counter.advance(now);
+            counter.rate(now)
+        })
+    }
+
+    /// Calculate difficulty increase for scope, adding one bit once the rate
+    /// reaches the threshold and another one each time it doubles.
+    pub fn increase(&self, scope: &str, threshold: u64) -> u32 {
+        match self.rate(scope) / threshold.max(1) {
+            0 => 0,
+            ratio => ratio.ilog2() + 1,
+        }
+    }
+}
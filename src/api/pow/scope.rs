@@ -0,0 +1,43 @@
+//! Proof of work scopes with separately configured difficulty.
+
+use crate::config::APIConfig;
+
+/// Proof of work scope marker trait.
+pub trait Scope: Send + Sync + 'static {
+    /// Name of the scope used for issuing challenges.
+    const NAME: &'static str;
+
+    /// Configured base difficulty of the scope.
+    fn difficulty(config: &APIConfig) -> u32;
+}
+
+/// Registration scope.
+#[derive(Clone, Debug)]
+pub struct Register;
+impl Scope for Register {
+    const NAME: &'static str = "register";
+
+    fn difficulty(config: &APIConfig) -> u32 {
+        config.pow_register_difficulty
+    }
+}
+
+/// Password reset scope.
+#[derive(Clone, Debug)]
+pub struct PasswordReset;
+impl Scope for PasswordReset {
+    const NAME: &'static str = "password_reset";
+
+    fn difficulty(config: &APIConfig) -> u32 {
+        config.pow_password_reset_difficulty
+    }
+}
+
+/// Get configured base difficulty of scope by its name.
+pub fn difficulty(config: &APIConfig, name: &str) -> Option<u32> {
+    match name {
+        Register::NAME => Some(Register::difficulty(config)),
+        PasswordReset::NAME => Some(PasswordReset::difficulty(config)),
+        _ => None,
+    }
+}
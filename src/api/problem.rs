@@ -0,0 +1,189 @@
+//! API error type rendered as RFC 7807 problem details.
+
+use rocket::{
+    catch, catchers, error,
+    http::{ContentType, Header, Status},
+    response::{self, Responder},
+    serde::json::Json,
+    Catcher, Request, Response,
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+use validator::ValidationErrors;
+
+use crate::mail;
+
+/// Problem details response body.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Problem {
+    #[serde(rename = "type")]
+    #[schema(example = "about:blank")]
+    pub kind: String,
+
+    #[schema(example = "Unprocessable Entity")]
+    pub title: String,
+
+    #[schema(example = 422)]
+    pub status: u16,
+
+    #[schema(example = "validation_failed")]
+    pub code: String,
+
+    #[schema(example = "Request body failed validation")]
+    pub detail: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>, example = json!({
+        "email": [{ "code": "email", "message": null, "params": {
+            "value": "alice"
+        } }]
+    }))]
+    pub errors: Option<ValidationErrors>,
+}
+
+/// API error enum.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{detail}")]
+    Status { status: Status, code: String, detail: String },
+
+    #[error("too many requests, retry after {retry_after} seconds")]
+    TooManyRequests { retry_after: u64 },
+
+    #[error("request body failed validation")]
+    Validation { #[from] source: ValidationErrors },
+
+    #[error("{context}")]
+    Internal { context: &'static str, source: Box<Internal> },
+}
+
+/// Internal server error sources.
+#[derive(Debug, thiserror::Error)]
+pub enum Internal {
+    #[error("database error")]
+    Database { #[from] source: surrealdb::Error },
+
+    #[error("mail error")]
+    Mail { #[from] source: mail::Error },
+
+    #[error("missing query result")]
+    Missing,
+}
+
+impl From<Box<surrealdb::Error>> for Internal {
+    fn from(source: Box<surrealdb::Error>) -> Self {
+        (*source).into()
+    }
+}
+
+/// Extension trait for attaching context to internal errors.
+pub trait Context<T> {
+    /// Convert error into internal API error with context message.
+    fn context(self, context: &'static str) -> Result<T, Error>;
+}
+
+impl<T, E: Into<Internal>> Context<T> for Result<T, E> {
+    fn context(self, context: &'static str) -> Result<T, Error> {
+        self.map_err(|err| Error::Internal {
+            context, source: Box::new(err.into()),
+        })
+    }
+}
+
+impl<T> Context<T> for Option<T> {
+    fn context(self, context: &'static str) -> Result<T, Error> {
+        self.ok_or(Error::Internal {
+            context, source: Box::new(Internal::Missing),
+        })
+    }
+}
+
+impl Error {
+    /// Create error with status, machine-readable code, and human message.
+    pub fn new(status: Status, code: &str, detail: &str) -> Self {
+        Self::Status { status, code: code.into(), detail: detail.into() }
+    }
+
+    /// Get HTTP status of the error.
+    pub fn status(&self) -> Status {
+        match self {
+            Self::Status { status, .. } => *status,
+            Self::TooManyRequests { .. } => Status::TooManyRequests,
+            Self::Validation { .. } => Status::UnprocessableEntity,
+            Self::Internal { .. } => Status::InternalServerError,
+        }
+    }
+
+    /// Convert error into problem details body.
+    pub fn problem(self) -> Problem {
+        let status = self.status();
+        let title = status.reason().unwrap_or("Unknown Error").to_string();
+        let (code, detail, errors) = match self {
+            Self::Status { code, detail, .. } => (code, detail, None),
+            Self::TooManyRequests { .. } => (
+                "too_many_requests".into(),
+                "Too many failed attempts, retry later".into(),
+                None,
+            ),
+            Self::Validation { source } => (
+                "validation_failed".into(),
+                "Request body failed validation".into(),
+                Some(source),
+            ),
+            Self::Internal { .. } => (
+                "internal_error".into(),
+                "The server encountered an internal error".into(),
+                None,
+            ),
+        };
+
+        Problem {
+            kind: "about:blank".into(), status: status.code,
+            title, code, detail, errors,
+        }
+    }
+}
+
+impl From<Status> for Error {
+    fn from(status: Status) -> Self {
+        let reason = status.reason().unwrap_or("Unknown Error");
+        let code = reason.to_lowercase().replace([' ', '-'], "_");
+        Self::Status { status, code, detail: reason.into() }
+    }
+}
+
+impl<'r> Responder<'r, 'static> for Error {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        // log internal errors with their context
+        if let Self::Internal { context, source } = &self {
+            error!("{context}: {source:?}");
+        }
+
+        // tell clients when to retry rate limited requests
+        let retry_after = match &self {
+            Self::TooManyRequests { retry_after } =>
+                Some(Header::new("Retry-After", retry_after.to_string())),
+            _ => None,
+        };
+
+        // respond with problem details
+        let status = self.status();
+        let mut response =
+            Response::build_from(Json(self.problem()).respond_to(req)?);
+        if let Some(header) = retry_after { response.header(header); }
+        response.status(status)
+            .header(ContentType::new("application", "problem+json"))
+            .ok()
+    }
+}
+
+/// Assemble API error catchers.
+pub fn catchers() -> Vec<Catcher> {
+    catchers![default]
+}
+
+/// Render any error status as problem details.
+#[catch(default)]
+fn default(status: Status, _: &Request<'_>) -> Error {
+    status.into()
+}
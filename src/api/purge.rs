@@ -0,0 +1,58 @@
+//! Background purge of users pending deletion, expired data, and old audit
+//! log entries.
+
+use std::time::Duration;
+
+use rocket::{error, fairing::AdHoc, tokio::{self, time}};
+use surrealdb::{engine::any::Any, sql, Surreal};
+
+use crate::config::APIConfig;
+
+/// Spawn task on liftoff purging users whose grace period has passed.
+pub fn mount() -> AdHoc {
+    AdHoc::on_liftoff("Purge Deleted Users", |rocket| Box::pin(async move {
+        // get database and API config managed by rocket
+        let (db, config) = match (
+            rocket.state::<Surreal<Any>>(), rocket.state::<APIConfig>(),
+        ) {
+            (Some(db), Some(config)) => (db.clone(), config),
+            _ => { error!("Error getting database or API config"); return; }
+        };
+        let grace = sql::Duration::from_days(config.deletion_grace_days);
+        let retention = sql::Duration::from_days(config.audit_retention_days);
+        let period = Duration::from_secs(config.deletion_purge_interval.max(1));
+
+        // purge periodically, starting immediately
+        tokio::spawn(async move {
+            let mut interval = time::interval(period);
+            loop {
+                interval.tick().await;
+                if let Err(err) = purge(&db, grace, retention).await {
+                    error!("SurrealDB purge: {err:?}");
+                }
+            }
+        });
+    }))
+}
+
+/// Delete users pending deletion for longer than the grace period, expired
+/// login sessions and challenges, restore tokens, data exports, member
+/// invitations, ownership transfers, API keys, and spent proof of work
+/// challenges, and audit events older than the retention period.
+pub async fn purge(
+    db: &Surreal<Any>, grace: sql::Duration, retention: sql::Duration,
+) -> Result<(), surrealdb::Error> {
+    db.query("
+        DELETE user WHERE deleted AND deleted < time::now() - $grace;
+        DELETE login WHERE expires < time::now();
+        DELETE login_challenge WHERE expires < time::now();
+        DELETE account_restore WHERE expires < time::now();
+        DELETE data_export WHERE expires < time::now();
+        DELETE membership_invitation WHERE expires < time::now();
+        DELETE ownership_transfer WHERE expires < time::now();
+        DELETE api_key WHERE expires AND expires < time::now();
+        DELETE pow_challenge WHERE expires < time::now();
+        DELETE audit_event WHERE created < time::now() - $retention;
+    ").bind(("grace", grace)).bind(("retention", retention)).await?.check()?;
+    Ok(())
+}
@@ -0,0 +1,48 @@
+//! Role route components.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::{Validate, ValidationError};
+
+/// Role creation input body.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct RoleIn {
+    /// Lowercase name of 2 to 32 letters, digits, underscores, or hyphens.
+    #[schema(example = "moderator")]
+    #[validate(custom(function = "validate_name"))]
+    pub name: String,
+
+    #[schema(example = json!(["users:list", "users:suspend"]))]
+    #[validate(custom(function = "super::super::permission::validate"))]
+    pub permissions: Vec<String>,
+}
+
+/// Role update input body.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct RoleUpdateIn {
+    #[schema(example = json!(["users:list", "users:suspend"]))]
+    #[validate(custom(function = "super::super::permission::validate"))]
+    pub permissions: Vec<String>,
+}
+
+/// Role output body.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RoleOut {
+    #[schema(example = "moderator")]
+    pub name: String,
+
+    #[schema(example = json!(["users:list", "users:suspend"]))]
+    pub permissions: Vec<String>,
+
+    /// Whether the role is built-in and can neither be updated nor deleted.
+    #[schema(example = false)]
+    pub builtin: bool,
+}
+
+/// Validate role name.
+fn validate_name(value: &str) -> Result<(), ValidationError> {
+    let valid = (2..=32).contains(&value.len()) && value.chars().all(|c| {
+        c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-'
+    });
+    valid.then_some(()).ok_or(ValidationError::new("invalid_name"))
+}
@@ -0,0 +1,61 @@
+//! Role creation route.
+
+use rocket::{http::Status, post, serde::json::Json};
+use validator::Validate;
+
+use crate::database::{Database, Take};
+use super::{
+    super::{
+        login::{Login, Perm}, permission::RolesManage,
+        problem::{Context, Error},
+    },
+    components::{RoleIn, RoleOut},
+};
+
+#[utoipa::path(
+    context_path = "/api/roles",
+    request_body = RoleIn,
+    responses(
+        (status = 201, description = "Created role", body = RoleOut),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Insufficient privileges"),
+        (status = 409, description = "Role already exists"),
+        (status = 422, description = "Invalid role data"),
+    ),
+    security(("login" = [])),
+    tag = "roles",
+)]
+
+/// POST /api/roles
+///
+/// Create custom role with the given permissions, which must all be granted
+/// to the logged in user. Requires the `roles:manage` permission.
+#[post("/", data = "<data>")]
+pub async fn route(
+    user: Login<Perm<RolesManage>>, db: &Database, data: Json<RoleIn>,
+) -> Result<(Status, Json<RoleOut>), Error> {
+    // validate input
+    data.validate()?;
+
+    // restrict permissions to ones granted to the logged in user
+    data.permissions.iter().all(|permission| user.can(permission))
+        .then_some(()).ok_or(Status::Forbidden)?;
+
+    // query database to create role unless it exists
+    let role: Option<RoleOut> = db.query("
+        let $role = type::thing('role', $name);
+
+        if !$role.id then (
+            CREATE ONLY $role SET permissions = $perms
+            RETURN meta::id(id) AS name, permissions, builtin
+        ) end;
+    ").bind(("name", &data.name)).bind(("perms", &data.permissions))
+        .await.take(1)
+        .context("error creating role")?;
+
+    // return created role or conflict error
+    let role = role.ok_or(Error::new(
+        Status::Conflict, "role_exists", "Role already exists",
+    ))?;
+    Ok((Status::Created, Json(role)))
+}
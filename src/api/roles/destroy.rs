@@ -0,0 +1,78 @@
+//! Role deletion route.
+
+use rocket::{delete, http::Status};
+
+use crate::database::{Database, Take};
+use super::{
+    super::{
+        login::{Login, Perm}, permission::RolesManage,
+        problem::{Context, Error},
+    },
+    fetch,
+};
+
+#[utoipa::path(
+    context_path = "/api/roles",
+    responses(
+        (status = 204, description = "Role deleted"),
+        (status = 401, description = "Invalid login session"),
+        (
+            status = 403,
+            description = "Insufficient privileges or built-in role",
+        ),
+        (status = 404, description = "Role not found"),
+        (status = 409, description = "Role still assigned"),
+    ),
+    security(("login" = [])),
+    tag = "roles",
+)]
+
+/// DELETE /api/roles/{name}
+///
+/// Delete custom role by its name. Built-in roles and roles still assigned to
+/// users, organization members, or pending invitations cannot be deleted.
+/// Requires the `roles:manage` permission.
+#[delete("/<name>")]
+pub async fn route(
+    _user: Login<Perm<RolesManage>>, db: &Database, name: &str,
+) -> Result<Status, Error> {
+    // reject unknown and built-in roles
+    let role = fetch(db, name).await?.ok_or(Status::NotFound)?;
+    if role.builtin {
+        return Err(Error::new(
+            Status::Forbidden, "builtin_role",
+            "Built-in roles cannot be deleted",
+        ));
+    }
+
+    // query database to delete role unless it is still assigned
+    let deleted: Option<bool> = db.query("
+        DELETE invitation WHERE expires < time::now();
+        DELETE membership_invitation WHERE expires < time::now();
+
+        let $assigned = array::flatten([
+            (SELECT id FROM user WHERE role = $name),
+            (SELECT id FROM invitation WHERE data.role = $name),
+            (SELECT id FROM membership WHERE role = $name),
+            (SELECT id FROM membership_invitation WHERE role = $name),
+        ]);
+
+        if !$assigned {
+            DELETE type::thing('role', $name);
+            true;
+        } else {
+            false;
+        };
+    ").bind(("name", name))
+        .await.take(3)
+        .context("error deleting role")?;
+
+    // return success or conflict error
+    match deleted {
+        Some(true) => Ok(Status::NoContent),
+        _ => Err(Error::new(
+            Status::Conflict, "role_in_use",
+            "Role is still assigned to users, members, or invitations",
+        )),
+    }
+}
@@ -0,0 +1,44 @@
+//! Role listing route.
+
+use rocket::{get, serde::json::Json};
+
+use crate::database::{Database, Take};
+use super::{
+    super::{
+        login::{Login, Perm}, permission::RolesManage,
+        problem::{Context, Error},
+    },
+    components::RoleOut,
+};
+
+#[utoipa::path(
+    context_path = "/api/roles",
+    responses((
+        status = 200, description = "List of roles", body = Vec<RoleOut>,
+    ), (
+        status = 401, description = "Invalid login session",
+    ), (
+        status = 403, description = "Insufficient privileges",
+    )),
+    security(("login" = [])),
+    tag = "roles",
+)]
+
+/// GET /api/roles
+///
+/// List built-in and custom roles with their permissions. Requires the
+/// `roles:manage` permission.
+#[get("/")]
+pub async fn route(
+    _user: Login<Perm<RolesManage>>, db: &Database,
+) -> Result<Json<Vec<RoleOut>>, Error> {
+    // query database for all roles
+    let roles: Vec<RoleOut> = db.query("
+        SELECT meta::id(id) AS name, permissions, builtin FROM role
+        ORDER BY name
+    ").await.take(0)
+        .context("error retrieving roles")?;
+
+    // return roles
+    Ok(Json(roles))
+}
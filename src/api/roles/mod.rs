@@ -0,0 +1,51 @@
+//! Role routes for managing custom roles and their permissions.
+
+use rocket::{http::Status, routes, Route};
+
+use crate::database::{Database, Take};
+use super::{login::{Login, Role}, problem::{Context, Error}};
+use components::RoleOut;
+
+pub mod components;
+pub mod index;
+pub mod create;
+pub mod update;
+pub mod destroy;
+
+/// Assemble role routes.
+pub fn routes() -> Vec<Route> {
+    routes![index::route, create::route, update::route, destroy::route]
+}
+
+/// Fetch role by its name.
+async fn fetch(db: &Database, name: &str) -> Result<Option<RoleOut>, Error> {
+    db.query("
+        SELECT meta::id(id) AS name, permissions, builtin
+        FROM ONLY type::thing('role', $name)
+    ").bind(("name", name))
+        .await.take(0)
+        .context("error retrieving role")
+}
+
+/// Check that role exists and that the logged in user outranks its holders,
+/// which is required for assigning it.
+pub async fn assignable<R: Role>(
+    user: &Login<R>, db: &Database, role: &str,
+) -> Result<(), Error> {
+    user.outranks(&permissions(db, role).await?).then_some(())
+        .ok_or(Status::Forbidden.into())
+}
+
+/// Fetch permissions of role to be assigned by its name.
+pub async fn permissions(
+    db: &Database, role: &str,
+) -> Result<Vec<String>, Error> {
+    let permissions: Option<Vec<String>> = db.query("
+        SELECT permissions FROM ONLY type::thing('role', $name)
+    ").bind(("name", role))
+        .await.take((0, "permissions"))
+        .context("error retrieving role")?;
+    permissions.ok_or(Error::new(
+        Status::UnprocessableEntity, "invalid_role", "Role does not exist",
+    ))
+}
@@ -0,0 +1,69 @@
+//! Role update route.
+
+use rocket::{http::Status, patch, serde::json::Json};
+use validator::Validate;
+
+use crate::database::{Database, Take};
+use super::{
+    super::{
+        login::{Login, Perm}, permission::RolesManage,
+        problem::{Context, Error},
+    },
+    components::{RoleOut, RoleUpdateIn},
+    fetch,
+};
+
+#[utoipa::path(
+    context_path = "/api/roles",
+    request_body = RoleUpdateIn,
+    responses(
+        (status = 200, description = "Updated role", body = RoleOut),
+        (status = 401, description = "Invalid login session"),
+        (
+            status = 403,
+            description = "Insufficient privileges or built-in role",
+        ),
+        (status = 404, description = "Role not found"),
+        (status = 422, description = "Invalid role data"),
+    ),
+    security(("login" = [])),
+    tag = "roles",
+)]
+
+/// PATCH /api/roles/{name}
+///
+/// Replace permissions of custom role by its name, which must all be granted
+/// to the logged in user. Built-in roles cannot be updated. Requires the
+/// `roles:manage` permission.
+#[patch("/<name>", data = "<data>")]
+pub async fn route(
+    user: Login<Perm<RolesManage>>, db: &Database, name: &str,
+    data: Json<RoleUpdateIn>,
+) -> Result<Json<RoleOut>, Error> {
+    // validate input
+    data.validate()?;
+
+    // restrict permissions to ones granted to the logged in user
+    data.permissions.iter().all(|permission| user.can(permission))
+        .then_some(()).ok_or(Status::Forbidden)?;
+
+    // reject unknown and built-in roles
+    let role = fetch(db, name).await?.ok_or(Status::NotFound)?;
+    if role.builtin {
+        return Err(Error::new(
+            Status::Forbidden, "builtin_role",
+            "Built-in roles cannot be updated",
+        ));
+    }
+
+    // query database to update role
+    let role: Option<RoleOut> = db.query("
+        UPDATE ONLY type::thing('role', $name) SET permissions = $perms
+        RETURN meta::id(id) AS name, permissions, builtin
+    ").bind(("name", name)).bind(("perms", &data.permissions))
+        .await.take(0)
+        .context("error updating role")?;
+
+    // return updated role
+    role.map(Json).ok_or(Status::NotFound.into())
+}
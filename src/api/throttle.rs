@@ -0,0 +1,124 @@
+//! Failed login attempt tracking with exponential backoff.
+
+use serde::Deserialize;
+use surrealdb::{engine::any::Any, sql::Duration, Surreal};
+
+use crate::{config::APIConfig, database::Take};
+use super::{client::Client, problem::{Context, Error}};
+
+/// Database response type for counted attempts.
+#[derive(Deserialize)]
+struct Attempt {
+    failures: u64,
+}
+
+/// Failed login attempt tracker for an email address and a client IP.
+pub struct Throttle {
+    email: String,
+    ip: Option<String>,
+    failures: u64,
+}
+
+impl Throttle {
+    /// Create tracker for login attempts to an email address by a client.
+    pub fn new(email: &str, client: &Client) -> Self {
+        let email = format!("email:{}", email.to_lowercase());
+        let ip = client.ip.as_ref().map(|ip| format!("ip:{ip}"));
+        Self { email, ip, failures: 0 }
+    }
+
+    /// Count attempt for client IP and email address before verifying the
+    /// credentials, and return too many requests error while either is
+    /// locked. Attempts count as failures until reset, so that concurrent
+    /// attempts cannot slip past the lockout.
+    pub async fn attempt(
+        &mut self, db: &Surreal<Any>, config: &APIConfig,
+    ) -> Result<(), Error> {
+        // forget expired failures
+        db.query("
+            DELETE login_attempt WHERE locked_until < time::now()
+                AND last_failure < time::now() - $window;
+        ").bind(("window", Duration::from_secs(config.login_failure_window)))
+            .await.check()
+            .context("error executing login attempt cleanup query")?;
+
+        // count attempt for client IP first to not lock email addresses from
+        // locked clients
+        if let Some(ip) = &self.ip {
+            record(db, config, ip, config.login_max_ip_failures).await?;
+        }
+        self.failures =
+            record(db, config, &self.email, config.login_max_failures).await?;
+        Ok(())
+    }
+
+    /// Return whether the failed attempt got the email address locked for
+    /// the first time.
+    pub fn fail(&self, config: &APIConfig) -> bool {
+        self.failures == config.login_max_failures
+    }
+
+    /// Forget failed attempts for the email address, and discount the
+    /// successful attempt for the client IP.
+    pub async fn reset(
+        &self, db: &Surreal<Any>, config: &APIConfig,
+    ) -> Result<(), Error> {
+        db.query("
+            DELETE type::thing('login_attempt', $email);
+
+            if $ip {
+                UPDATE type::thing('login_attempt', $ip) SET
+                    failures -= 1,
+                    locked_until = if failures < $limit
+                        then time::now() else locked_until end
+                WHERE failures > 0;
+            };
+        ").bind(("email", &self.email)).bind(("ip", &self.ip))
+            .bind(("limit", config.login_max_ip_failures))
+            .await.check()
+            .context("error executing login attempt reset query")?;
+        Ok(())
+    }
+}
+
+/// Count attempt for key unless locked, lock further attempts once the limit
+/// is reached with the lockout doubling with each excess attempt, and return
+/// the number of attempts within the failure window. This happens in a single
+/// update, which decides whether the attempt is counted or rejected.
+async fn record(
+    db: &Surreal<Any>, config: &APIConfig, key: &str, limit: u64,
+) -> Result<u64, Error> {
+    let mut response = db.query("
+        UPDATE type::thing('login_attempt', $key) SET
+            failures = if last_failure > time::now() - $window
+                then failures + 1 else 1 end,
+            last_failure = time::now(),
+            locked_until = time::now() + (if failures >= $limit then
+                duration::from::secs(math::min([
+                    $lockout * math::pow(2, math::min([failures - $limit, 32])),
+                    $lockout_max,
+                ]))
+            else 0s end)
+        WHERE !(locked_until > time::now())
+        RETURN AFTER;
+
+        SELECT VALUE duration::secs(locked_until - time::now())
+        FROM ONLY type::thing('login_attempt', $key);
+    ").bind(("key", key)).bind(("limit", limit))
+        .bind(("window", Duration::from_secs(config.login_failure_window)))
+        .bind(("lockout", config.login_lockout))
+        .bind(("lockout_max", config.login_lockout_max))
+        .await.context("error executing login attempt query")?;
+    let attempt: Option<Attempt> = response.take(0)
+        .context("error counting login attempt")?;
+    let remaining: Option<i64> = response.take(1)
+        .context("error retrieving remaining lockout")?;
+
+    // return failures or remaining lockout if not counted
+    match attempt {
+        Some(attempt) => Ok(attempt.failures),
+        None => Err(Error::TooManyRequests {
+            retry_after: remaining.unwrap_or(0).max(0) as u64 + 1,
+        }),
+    }
+}
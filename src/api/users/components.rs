@@ -1,10 +1,74 @@
 //! User route components.
 
+use rocket::{FromForm, FromFormField};
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use surrealdb::sql::Datetime;
+use utoipa::{IntoParams, ToSchema};
 use validator::{Validate, ValidationError};
 
 use crate::database::Id;
+use super::super::page::Order;
+
+/// User listing filter and sort query parameters.
+#[derive(Debug, FromForm, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UserFilter {
+    /// Only list users with this role.
+    #[param(example = "admin")]
+    pub role: Option<String>,
+
+    /// Only list users whose name or email address contains this text.
+    #[param(example = "alice")]
+    pub q: Option<String>,
+
+    /// Field to sort by.
+    #[field(default_with = Some(UserSort::Name))]
+    #[param(inline)]
+    pub sort: UserSort,
+
+    /// Sort order.
+    #[field(default_with = Some(Order::Asc))]
+    #[param(inline)]
+    pub order: Order,
+}
+
+/// User listing sort field.
+#[derive(Debug, Default, Clone, Copy, FromFormField, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum UserSort {
+    #[default]
+    Name,
+    Created,
+}
+
+impl UserSort {
+    /// Get SurrealQL field name to sort by.
+    pub fn field(self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::Created => "created",
+        }
+    }
+}
+
+/// User creation input body.
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct UserCreateIn {
+    #[schema(example = "Alice")]
+    #[validate(length(min = 2))]
+    pub name: String,
+
+    #[schema(example = "alice@example.com")]
+    #[validate(email)]
+    pub email: String,
+
+    #[schema(example = "user")]
+    pub role: Option<String>,
+
+    #[schema(example = "supersecret")]
+    #[validate(length(min = 8))]
+    pub password: Option<String>,
+}
 
 /// User update input body.
 #[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
@@ -15,12 +79,11 @@ pub struct UserIn {
     pub name: Option<String>,
 
     #[schema(example = "user")]
-    #[validate(custom(function = "validate_role"))]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub role: Option<String>,
 }
 
-// User output body.
+/// User output body.
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UserOut {
     pub id: Id<String>,
@@ -30,10 +93,64 @@ pub struct UserOut {
 
     #[schema(example = "user")]
     pub role: String,
+
+    #[schema(format = DateTime, example = "2024-04-13T12:25:04Z")]
+    pub created: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disabled: Option<DisabledOut>,
+
+    #[schema(format = DateTime, example = "2024-04-13T12:25:04Z")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted: Option<String>,
+}
+
+/// User suspension input body.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct SuspendIn {
+    #[schema(example = "Spamming other users")]
+    #[validate(length(min = 1, max = 500))]
+    pub reason: String,
+
+    /// End of the suspension, which is indefinite if omitted.
+    #[schema(format = DateTime, example = "2024-05-13T12:25:04Z")]
+    #[validate(custom(function = "validate_until"))]
+    pub until: Option<String>,
+}
+
+/// User suspension output body.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DisabledOut {
+    #[schema(example = "Spamming other users")]
+    pub reason: String,
+
+    #[schema(format = DateTime, example = "2024-04-13T12:25:04Z")]
+    pub since: String,
+
+    #[schema(format = DateTime, example = "2024-05-13T12:25:04Z")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+}
+
+/// Invitation output body.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct InvitationOut {
+    #[schema(example = "Alice")]
+    pub name: String,
+
+    #[schema(example = "alice@example.com")]
+    pub email: String,
+
+    #[schema(example = "user")]
+    pub role: String,
+
+    #[schema(format = DateTime, example = "2024-04-20T12:25:04Z")]
+    pub expires: String,
 }
 
-/// Validate user role.
-fn validate_role(value: &str) -> Result<(), ValidationError> {
-    (value == "user" || value == "admin").then_some(())
-        .ok_or(ValidationError::new("invalid_role"))
+/// Validate suspension end being a datetime in the future.
+fn validate_until(value: &str) -> Result<(), ValidationError> {
+    Datetime::try_from(value).ok()
+        .filter(|until| *until > Datetime::default()).map(|_| ())
+        .ok_or(ValidationError::new("invalid_until"))
 }
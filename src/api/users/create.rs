@@ -0,0 +1,173 @@
+//! User creation and invitation route.
+
+use rocket::{http::Status, post, serde::json::Json, Responder};
+use serde::Deserialize;
+use validator::Validate;
+
+use crate::{database::{Database, Take}, mail::Mail};
+use super::{
+    super::{
+        audit::Audit, client::Client, login::{Login, Perm},
+        permission::UsersCreate, problem::{Context, Error},
+        roles::assignable,
+    },
+    components::{InvitationOut, UserCreateIn, UserOut},
+};
+
+/// Database response type for invitations.
+#[derive(Deserialize)]
+struct DbInvitation {
+    token: String,
+    #[serde(flatten)]
+    invitation: InvitationOut,
+}
+
+/// User creation response with created user or pending invitation.
+#[derive(Responder)]
+pub enum CreateResponse {
+    #[response(status = 201)]
+    Created(Json<UserOut>),
+
+    #[response(status = 202)]
+    Invited(Json<InvitationOut>),
+}
+
+#[utoipa::path(
+    context_path = "/api/users",
+    request_body = UserCreateIn,
+    responses(
+        (status = 201, description = "Created user", body = UserOut),
+        (status = 202, description = "Invitation sent", body = InvitationOut),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Insufficient privileges"),
+        (status = 409, description = "Email address already in use"),
+        (status = 422, description = "Invalid user data"),
+    ),
+    security(("login" = [])),
+    tag = "users",
+)]
+
+/// POST /api/users
+///
+/// Create user with the given role, which defaults to "user". Requires the
+/// `users:create` permission and outranking holders of the assigned role.
+///
+/// If no password is given, an invitation is sent to the email address
+/// instead, containing a token that needs to be posted to
+/// `/api/auth/accept` together with a password within 7 days. Both are
+/// recorded in the audit log.
+#[post("/", data = "<data>")]
+pub async fn route(
+    user: Login<Perm<UsersCreate>>, db: &Database, mail: &Mail,
+    client: Client, data: Json<UserCreateIn>,
+) -> Result<CreateResponse, Error> {
+    // validate input
+    data.validate()?;
+
+    // restrict assigned role to ones outranked by the logged in user
+    let role = data.role.as_deref().unwrap_or("user");
+    assignable(&user, db, role).await?;
+
+    // create user directly or invite them if no password given
+    let response = match &data.password {
+        Some(password) => {
+            let audit = Audit::new("user.create", &client).actor(&user.id.0);
+            create(db, audit, &data, role, password).await?
+                .map(|user| CreateResponse::Created(Json(user)))
+        },
+        None => {
+            let audit = Audit::new("user.invite", &client).actor(&user.id.0);
+            invite(db, audit, &data, role).await?.map(|result| {
+                mail.spawn(&data.email, "invite-user", &[
+                    ("name", &data.name), ("token", &result.token),
+                ]);
+                CreateResponse::Invited(Json(result.invitation))
+            })
+        },
+    };
+
+    // return response or conflict error if email address is in use
+    response.ok_or(Error::new(
+        Status::Conflict, "email_taken", "Email address already in use",
+    ))
+}
+
+/// Create user unless email address is in use.
+async fn create(
+    db: &Database, audit: Audit, data: &UserCreateIn, role: &str,
+    password: &str,
+) -> Result<Option<UserOut>, Error> {
+    audit.query(db, "
+        DELETE registration WHERE expires < time::now();
+        DELETE invitation WHERE expires < time::now();
+
+        let $existing = array::concat((
+            SELECT id FROM registration
+            WHERE data.email = string::lowercase($email)
+        ), (
+            SELECT id FROM invitation
+            WHERE data.email = string::lowercase($email)
+        ), (
+            SELECT id FROM user
+            WHERE email = string::lowercase($email)
+        ));
+
+        let $audited = !$existing;
+
+        if $audited then (
+            CREATE ONLY user SET
+                name = $name, email = $email, role = $role,
+                password = crypto::argon2::generate($pass)
+        ) end;
+
+        let $target = (
+            SELECT VALUE id FROM ONLY user
+            WHERE email = string::lowercase($email) LIMIT 1
+        );
+    ").bind(("name", &data.name)).bind(("email", &data.email))
+        .bind(("role", role)).bind(("pass", password))
+        .await.take(4)
+        .context("error executing user creation query")
+}
+
+/// Create invitation and return its token unless email address is in use.
+async fn invite(
+    db: &Database, audit: Audit, data: &UserCreateIn, role: &str,
+) -> Result<Option<DbInvitation>, Error> {
+    audit.query(db, "
+        DELETE registration WHERE expires < time::now();
+        DELETE invitation WHERE expires < time::now();
+
+        let $existing = array::concat((
+            SELECT id FROM registration
+            WHERE data.email = string::lowercase($email)
+        ), (
+            SELECT id FROM invitation
+            WHERE data.email = string::lowercase($email)
+        ), (
+            SELECT id FROM user
+            WHERE email = string::lowercase($email)
+        ));
+
+        let $secret = rand::string();
+        let $audited = !$existing;
+        let $diff = {
+            after: {
+                name: $name, email: string::lowercase($email), role: $role,
+            },
+        };
+
+        if $audited then (
+            CREATE ONLY invitation SET
+                data = { name: $name, email: $email, role: $role },
+                token = crypto::sha256($secret),
+                expires = time::now() + 7d
+            RETURN
+                $secret AS token, data.name AS name, data.email AS email,
+                data.role AS role, expires
+        ) end;
+    ").bind(("name", &data.name)).bind(("email", &data.email))
+        .bind(("role", role))
+        .await.take(6)
+        .context("error executing invitation query")
+}
@@ -1,14 +1,31 @@
 //! User deletion route.
 
 use rocket::{delete, http::Status};
+use serde::Deserialize;
+use surrealdb::sql::Duration;
 
-use crate::database::{Database, Record};
-use super::super::login::{Login, Owner, User};
+use crate::{database::{Database, Take}, mail::Mail};
+use super::{
+    super::{
+        audit::Audit, client::Client, login::{Direct, Login},
+        permission::{Permission, UsersDelete}, problem::{Context, Error},
+        Config,
+    },
+    target,
+};
+
+/// Database response type.
+#[derive(Deserialize)]
+struct DbOutput {
+    token: Option<String>,
+    name: String,
+    email: String,
+}
 
 #[utoipa::path(
     context_path = "/api/users",
     responses(
-        (status = 204, description = "User deleted"),
+        (status = 204, description = "User scheduled for deletion"),
         (status = 404, description = "User not found"),
         (status = 401, description = "Invalid login session"),
         (status = 403, description = "Insufficient privileges"),
@@ -19,18 +36,63 @@ use super::super::login::{Login, Owner, User};
 
 /// DELETE /api/users/{id}
 ///
-/// Delete user account by their ID. Requires owner privileges except when
-/// deleting the currently logged in user.
+/// Delete user account by their ID. Requires the `users:delete` permission and
+/// outranking the user except when deleting the currently logged in user.
+///
+/// The user is logged out and only marked as pending deletion until they are
+/// purged after the configured grace period. Users deleting themselves receive
+/// an email with a token for restoring their account via `/api/auth/restore`,
+/// while deletions by others can only be undone via `/api/users/{id}/restore`.
+/// The deletion is recorded in the audit log. Impersonation sessions cannot
+/// delete accounts.
 #[delete("/<id>")]
-pub async fn route(user: Login<User>, db: &Database, id: &str) -> Status {
-    // only allow deleting self if not owner
-    let authorized = user.is(Owner) || user.id == id;
-    if !authorized { return Status::Forbidden; }
+pub async fn route(
+    user: Login<Direct>, db: &Database, mail: &Mail, config: &Config,
+    client: Client, id: &str,
+) -> Result<Status, Error> {
+    // only allow deleting self without permission
+    let authorized = user.can(UsersDelete::NAME) || user.id == id;
+    if !authorized { return Err(Status::Forbidden.into()); }
+    if user.id != id { target(&user, db, id).await?; }
+
+    // query database to mark user as deleted and log them out
+    let grace = Duration::from_days(config.deletion_grace_days);
+    let audit = Audit::new("user.delete", &client).actor(&user.id.0).target(id);
+    let result: Option<DbOutput> = audit.query(db, "
+        let $user = type::thing('user', $uid);
+        let $audited = !!$user.id AND !$user.deleted;
+
+        if $audited {
+            UPDATE $user SET deleted = time::now();
+            DELETE login WHERE user = $user OR impersonator = $user;
+            DELETE login_challenge WHERE user = $user;
+            DELETE account_restore WHERE user = $user;
+
+            let $secret = if $own { rand::string() };
+            if $own {
+                (CREATE account_restore SET
+                    user = $user, token = crypto::sha256($secret),
+                    expires = time::now() + $grace);
+            };
+
+            {
+                \"token\": $secret, \"name\": $user.name,
+                \"email\": $user.email,
+            };
+        };
+    ").bind(("uid", id)).bind(("own", user.id == id)).bind(("grace", grace))
+        .await.take(2)
+        .context("error executing user deletion query")?;
+    let DbOutput { token, name, email } = result.ok_or(Status::NotFound)?;
 
-    // query database to delete user
-    let result: Option<Record> = db.delete(("user", id)).await
-        .expect("error deleting user");
+    // spawn job for sending restore token to users deleting themselves
+    if let Some(token) = token {
+        mail.spawn(&email, "account-deleted", &[
+            ("name", &name), ("token", &token),
+            ("days", &config.deletion_grace_days.to_string()),
+        ]);
+    }
 
-    // return success or not found status
-    result.map(|_| Status::NoContent).unwrap_or(Status::NotFound)
+    // return success status
+    Ok(Status::NoContent)
 }
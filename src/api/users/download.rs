@@ -0,0 +1,39 @@
+//! User data export download route.
+
+use rocket::{get, http::Status, serde::json::{Json, Value}};
+
+use crate::database::{Database, Take};
+use super::super::problem::{Context, Error};
+
+#[utoipa::path(
+    context_path = "/api/users",
+    responses(
+        (status = 200, description = "Personal data export", body = Object),
+        (status = 404, description = "Export not found"),
+    ),
+    tag = "users",
+)]
+
+/// GET /api/users/{id}/export/{token}
+///
+/// Download personal data export of a user by their ID using the token of the
+/// link sent by email, within 24 hours after the export.
+#[get("/<id>/export/<token>")]
+pub async fn route(
+    db: &Database, id: &str, token: &str,
+) -> Result<Json<Value>, Error> {
+    // query database for unexpired export
+    let result: Option<Value> = db.query("
+        SELECT VALUE data FROM ONLY data_export
+        WHERE token = crypto::sha256($tok)
+            AND user = type::thing('user', $uid) AND expires > time::now()
+        LIMIT 1
+    ").bind(("tok", token)).bind(("uid", id))
+        .await.take(0)
+        .context("error retrieving data export")?;
+
+    // return export or not found error
+    result.map(Json).ok_or(Error::new(
+        Status::NotFound, "export_not_found", "Export not found or expired",
+    ))
+}
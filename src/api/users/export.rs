@@ -0,0 +1,67 @@
+//! User data export route.
+
+use rocket::{get, http::Status, serde::json::{Json, Value}, State};
+
+use crate::database::{Database, Take};
+use super::{
+    super::{
+        audit::Audit, client::Client, export::Registry, login::{Direct, Login},
+        permission::{Permission, UsersExport}, problem::{Context, Error},
+    },
+    target,
+};
+
+#[utoipa::path(
+    context_path = "/api/users",
+    responses(
+        (status = 200, description = "Personal data export", body = Object),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Insufficient privileges"),
+        (status = 404, description = "User not found"),
+    ),
+    security(("login" = [])),
+    tag = "users",
+)]
+
+/// GET /api/users/{id}/export
+///
+/// Export all personal data stored about a user by their ID as a JSON
+/// document with one section per table. Requires the `users:export`
+/// permission and outranking the user except for exporting the currently
+/// logged in user, and is not available in impersonation sessions. Exports of
+/// other users are recorded in the audit log.
+#[get("/<id>/export")]
+pub async fn route(
+    user: Login<Direct>, db: &Database, client: Client,
+    registry: &State<Registry>, id: &str,
+) -> Result<Json<Value>, Error> {
+    let document = document(&user, db, registry, id).await?;
+
+    // record exports of other users
+    if user.id != id {
+        Audit::new("user.export", &client).actor(&user.id.0).target(id)
+            .query(db, "let $audited = true;").await.check()
+            .context("error recording user export")?;
+    }
+
+    Ok(Json(document))
+}
+
+/// Check that the logged in user may export the user by their ID and collect
+/// data from all registered sections.
+pub(super) async fn document(
+    user: &Login<Direct>, db: &Database, registry: &Registry, id: &str,
+) -> Result<Value, Error> {
+    // only allow exporting others with permission if outranking them
+    if user.id != id {
+        if !user.can(UsersExport::NAME) {
+            return Err(Status::Forbidden.into());
+        }
+        target(user, db, id).await?;
+    }
+
+    // collect data from all registered sections
+    registry.export(db, id).await
+        .context("error exporting user data")?
+        .ok_or(Status::NotFound.into())
+}
@@ -0,0 +1,79 @@
+//! User impersonation route.
+
+use rocket::{http::Status, post, serde::json::Json};
+use surrealdb::sql::Duration;
+
+use crate::database::{Database, Take};
+use super::{
+    super::{
+        audit::Audit, auth::components::LoginOut, client::Client,
+        login::{Login, Perm}, permission::UsersImpersonate,
+        problem::{Context, Error}, Config,
+    },
+    target,
+};
+
+#[utoipa::path(
+    context_path = "/api/users",
+    responses(
+        (status = 200, description = "Impersonation session", body = LoginOut),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Insufficient privileges"),
+        (status = 404, description = "User not found"),
+        (status = 409, description = "User suspended or deleted"),
+    ),
+    security(("login" = [])),
+    tag = "users",
+)]
+
+/// POST /api/users/{id}/impersonate
+///
+/// Create login session for acting as a user by their ID, which expires after
+/// the configured impersonation lifetime and can be revoked like any other
+/// session of the user. Routes changing credentials or deleting the account
+/// reject impersonation sessions. Requires the `users:impersonate` permission
+/// and outranking the user by the permissions of their role, and cannot be
+/// used while impersonating. The start of the impersonation is recorded in
+/// the audit log, as is its end when logging out of the session.
+#[post("/<id>/impersonate")]
+pub async fn route(
+    user: Login<Perm<UsersImpersonate>>, db: &Database, config: &Config,
+    client: Client, id: &str,
+) -> Result<Json<LoginOut>, Error> {
+    // forbid nested impersonation and check privileges on user
+    if user.impersonator.is_some() {
+        return Err(Status::Forbidden.into());
+    }
+    target(&user, db, id).await?;
+
+    // query database to create impersonation session of active user
+    let audit = Audit::new("user.impersonate_start", &client)
+        .actor(&user.id.0).target(id);
+    let result: Option<LoginOut> = audit.query(db, "
+        let $user = type::thing('user', $uid);
+        let $audited = !$user.deleted AND !($user.disabled AND (
+            !$user.disabled.until OR $user.disabled.until > time::now()
+        ));
+        let $secret = rand::string();
+
+        if $audited {
+            (CREATE ONLY login SET
+                user = $user, impersonator = type::thing('user', $iid),
+                token = crypto::sha256($secret),
+                expires = time::now() + $lifetime,
+                ip = $ip, user_agent = $agent
+            RETURN
+                user AS id, user.name AS name, user.role AS role,
+                $secret AS token, expires);
+        };
+    ").bind(("uid", id)).bind(("iid", &user.id.0))
+        .bind(("lifetime", Duration::from_secs(config.impersonation_lifetime)))
+        .bind(("ip", &client.ip)).bind(("agent", &client.user_agent))
+        .await.take(3)
+        .context("error executing impersonation query")?;
+
+    // return session or conflict error for inactive users
+    result.map(Json).ok_or(Error::new(
+        Status::Conflict, "user_inactive", "User is suspended or deleted",
+    ))
+}
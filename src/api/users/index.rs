@@ -3,25 +3,53 @@
 use rocket::{get, serde::json::Json};
 
 use crate::database::Database;
-use super::{super::login::{Admin, Login}, components::UserOut};
+use super::{
+    super::{
+        login::{Login, Perm}, page::{Page, Paginated},
+        permission::UsersList, problem::Error,
+    },
+    components::{UserFilter, UserOut},
+};
 
 #[utoipa::path(
     context_path = "/api/users",
+    params(Page, UserFilter),
     responses(
-        (status = 200, description = "List of users", body = Vec<UserOut>),
+        (status = 200, description = "Page of users", body = PaginatedUserOut),
         (status = 401, description = "Invalid login session"),
         (status = 403, description = "Insufficient privileges"),
+        (status = 422, description = "Invalid query parameters"),
     ),
     security(("login" = [])),
     tag = "users",
 )]
 
-/// GET /api/users/
+/// GET /api/users/?limit={limit}&offset={offset}&role={role}&q={q}&sort={sort}&order={order}
 ///
-/// List all users. Requires admin privileges.
-#[get("/")]
-pub async fn route(_user: Login<Admin>, db: &Database) -> Json<Vec<UserOut>> {
-    let users: Vec<UserOut> = db.select("user").await
-        .expect("error retrieving users");
-    Json(users)
+/// List users page by page, optionally filtered by role and by text contained
+/// in their name or email address, and sorted by name or creation date.
+/// Requires the `users:list` permission.
+#[get("/?<filter..>")]
+pub async fn route(
+    _user: Login<Perm<UsersList>>, db: &Database, page: Page,
+    filter: UserFilter,
+) -> Result<Json<Paginated<UserOut>>, Error> {
+    // assemble sort clause from fixed field names and keywords
+    let sort = format!("{} {}", filter.sort.field(), filter.order.keyword());
+
+    // query database for page of matching users and total count
+    let query = page.query(
+        "id, name, role, created, disabled, deleted",
+        "user WHERE (!$role OR role = $role)
+            AND (!$q OR string::contains(string::lowercase(name), $q)
+                OR string::contains(email, $q))",
+        &sort,
+    );
+    let users = page.fetch(db.query(query)
+        .bind(("role", &filter.role))
+        .bind(("q", filter.q.as_ref().map(|q| q.to_lowercase()))),
+    ).await?;
+
+    // return page in envelope
+    Ok(Json(users))
 }
@@ -0,0 +1,73 @@
+//! User data export by email route.
+
+use rocket::{http::Status, post, State};
+use serde::Deserialize;
+
+use crate::{database::{Database, Take}, mail::Mail};
+use super::{
+    super::{
+        audit::Audit, client::Client, export::Registry,
+        login::{Direct, Login}, problem::{Context, Error}, Config,
+    },
+    export::document,
+};
+
+/// Database response type for emailed exports.
+#[derive(Deserialize)]
+struct DbOutput {
+    token: String,
+    name: String,
+    email: String,
+}
+
+#[utoipa::path(
+    context_path = "/api/users",
+    responses(
+        (status = 202, description = "Download link sent by email"),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Insufficient privileges"),
+        (status = 404, description = "User not found"),
+    ),
+    security(("login" = [])),
+    tag = "users",
+)]
+
+/// POST /api/users/{id}/export
+///
+/// Export all personal data stored about a user by their ID like
+/// `GET /api/users/{id}/export`, but store the document for 24 hours and send
+/// a download link to the user's email address, which suits large exports.
+/// The export is recorded in the audit log.
+#[post("/<id>/export")]
+pub async fn route(
+    user: Login<Direct>, db: &Database, mail: &Mail, config: &Config,
+    client: Client, registry: &State<Registry>, id: &str,
+) -> Result<Status, Error> {
+    // collect data from all registered sections
+    let document = document(&user, db, registry, id).await?;
+
+    // query database to store export and return download token
+    let audit = Audit::new("user.export_mail", &client).actor(&user.id.0)
+        .target(id);
+    let result: Option<DbOutput> = audit.query(db, "
+        let $user = type::thing('user', $uid);
+        let $secret = rand::string();
+
+        (CREATE ONLY data_export SET
+            user = $user, token = crypto::sha256($secret), data = $data,
+            expires = time::now() + 24h
+        RETURN $secret AS token, user.name AS name, user.email AS email);
+    ").bind(("uid", id)).bind(("data", document))
+        .await.take(2)
+        .context("error storing data export")?;
+    let DbOutput { token, name, email } = result.ok_or(Status::NotFound)?;
+
+    // spawn job for sending download link
+    let url = format!(
+        "{}/api/users/{id}/export/{token}",
+        config.public_url.trim_end_matches('/'),
+    );
+    mail.spawn(&email, "data-export", &[("name", &name), ("url", &url)]);
+
+    Ok(Status::Accepted)
+}
@@ -1,14 +1,64 @@
 //! User routes.
 
-use rocket::{routes, Route};
+use rocket::{http::Status, routes, Route};
+use serde::Deserialize;
+
+use crate::database::{Database, Take};
+use super::{login::{Login, Role}, problem::{Context, Error}};
+use components::DisabledOut;
 
 pub mod components;
 pub mod index;
+pub mod create;
 pub mod show;
 pub mod update;
 pub mod destroy;
+pub mod restore;
+pub mod sessions;
+pub mod two_factor;
+pub mod suspend;
+pub mod unsuspend;
+pub mod export;
+pub mod mail_export;
+pub mod download;
+pub mod impersonate;
 
 /// Assemble user routes.
 pub fn routes() -> Vec<Route> {
-    routes![index::route, show::route, update::route, destroy::route]
+    routes![
+        index::route, create::route, show::route, update::route, destroy::route,
+        restore::route,
+        sessions::index::route, sessions::destroy::route,
+        sessions::clear::route, two_factor::route,
+        suspend::route, unsuspend::route, export::route, mail_export::route,
+        download::route, impersonate::route,
+    ]
+}
+
+/// Database response type for users targeted by admin actions.
+#[derive(Deserialize)]
+struct Target {
+    name: String,
+    email: String,
+    permissions: Vec<String>,
+    disabled: Option<DisabledOut>,
+}
+
+/// Fetch user by their ID and check that the logged in user is someone else
+/// and outranks them by the permissions of their roles.
+async fn target<R: Role>(
+    user: &Login<R>, db: &Database, id: &str,
+) -> Result<Target, Error> {
+    let target: Option<Target> = db.query("
+        SELECT
+            name, email, disabled,
+            (type::thing('role', role).permissions ?? []) AS permissions
+        FROM ONLY type::thing('user', $uid)
+    ").bind(("uid", id))
+        .await.take(0)
+        .context("error retrieving user")?;
+    let target = target.ok_or(Status::NotFound)?;
+
+    (user.id != id && user.outranks(&target.permissions))
+        .then_some(target).ok_or(Status::Forbidden.into())
 }
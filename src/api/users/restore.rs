@@ -0,0 +1,60 @@
+//! User restore route.
+
+use rocket::{http::Status, post, serde::json::Json};
+
+use crate::database::{Database, Take};
+use super::{
+    super::{
+        audit::Audit, client::Client, login::{Login, Perm},
+        permission::UsersDelete, problem::{Context, Error},
+    },
+    components::UserOut,
+    target,
+};
+
+#[utoipa::path(
+    context_path = "/api/users",
+    responses(
+        (status = 200, description = "Restored user", body = UserOut),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Insufficient privileges"),
+        (status = 404, description = "User not found"),
+        (status = 409, description = "User not pending deletion"),
+    ),
+    security(("login" = [])),
+    tag = "users",
+)]
+
+/// POST /api/users/{id}/restore
+///
+/// Restore user pending deletion by their ID before the configured grace
+/// period has passed, which is the only way to undo deletions by others.
+/// Requires the `users:delete` permission and outranking the user by the
+/// permissions of their role. The restore is recorded in the audit log.
+#[post("/<id>/restore")]
+pub async fn route(
+    user: Login<Perm<UsersDelete>>, db: &Database, client: Client, id: &str,
+) -> Result<Json<UserOut>, Error> {
+    // check privileges on user to be restored
+    target(&user, db, id).await?;
+
+    // query database to unmark user as deleted
+    let audit = Audit::new("user.restore", &client).actor(&user.id.0)
+        .target(id);
+    let result: Option<UserOut> = audit.query(db, "
+        let $user = type::thing('user', $uid);
+        let $audited = !!$user.deleted;
+
+        if $audited {
+            DELETE account_restore WHERE user = $user;
+            (UPDATE ONLY $user SET deleted = NONE RETURN AFTER);
+        };
+    ").bind(("uid", id))
+        .await.take(2)
+        .context("error executing user restore query")?;
+
+    // return restored user or conflict error
+    result.map(Json).ok_or(Error::new(
+        Status::Conflict, "not_deleted", "User is not pending deletion",
+    ))
+}
@@ -0,0 +1,57 @@
+//! User session clearing route.
+
+use rocket::{delete, http::Status};
+
+use crate::database::{Database, Take};
+use super::super::{
+    super::{
+        audit::Audit, client::Client, login::{Login, User},
+        permission::{Permission, SessionsRevoke},
+        problem::{Context, Error},
+    },
+    target,
+};
+
+#[utoipa::path(
+    context_path = "/api/users",
+    responses(
+        (status = 204, description = "Sessions revoked"),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Insufficient privileges"),
+        (status = 404, description = "User not found"),
+    ),
+    security(("login" = [])),
+    tag = "users",
+)]
+
+/// DELETE /api/users/{id}/sessions
+///
+/// Revoke all login sessions of a user by their ID except for the one used
+/// for this request. Requires the `sessions:revoke` permission and outranking
+/// the user except for revoking sessions of the currently logged in user. The
+/// revocation is recorded in the audit log.
+#[delete("/<id>/sessions")]
+pub async fn route(
+    user: Login<User>, db: &Database, client: Client, id: &str,
+) -> Result<Status, Error> {
+    // only allow revoking others' sessions with permission if outranking them
+    if user.id != id {
+        if !user.can(SessionsRevoke::NAME) {
+            return Err(Status::Forbidden.into());
+        }
+        target(&user, db, id).await?;
+    }
+
+    // delete sessions of specified user
+    let audit = Audit::new("user.sessions_revoke", &client).actor(&user.id.0)
+        .target(id);
+    audit.query(db, "
+        DELETE login
+        WHERE user = type::thing('user', $uid)
+            AND id != type::thing('login', $sid)
+    ").bind(("uid", id)).bind(("sid", &user.session.0))
+        .await.check()
+        .context("error revoking sessions")?;
+
+    Ok(Status::NoContent)
+}
@@ -0,0 +1,64 @@
+//! User session revocation route.
+
+use rocket::{delete, http::Status};
+
+use crate::database::{Database, Record, Take};
+use super::super::{
+    super::{
+        audit::Audit, client::Client, login::{Login, User},
+        permission::{Permission, SessionsRevoke},
+        problem::{Context, Error},
+    },
+    target,
+};
+
+#[utoipa::path(
+    context_path = "/api/users",
+    responses(
+        (status = 204, description = "Session revoked"),
+        (status = 404, description = "User or session not found"),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Insufficient privileges"),
+    ),
+    security(("login" = [])),
+    tag = "users",
+)]
+
+/// DELETE /api/users/{id}/sessions/{session}
+///
+/// Revoke login session of a user by their ID and the session ID. Requires
+/// the `sessions:revoke` permission and outranking the user except for
+/// revoking sessions of the currently logged in user. The revocation is
+/// recorded in the audit log.
+#[delete("/<id>/sessions/<session>")]
+pub async fn route(
+    user: Login<User>, db: &Database, client: Client, id: &str,
+    session: &str,
+) -> Result<Status, Error> {
+    // only allow revoking others' sessions with permission if outranking them
+    if user.id != id {
+        if !user.can(SessionsRevoke::NAME) {
+            return Err(Status::Forbidden.into());
+        }
+        target(&user, db, id).await?;
+    }
+
+    // delete session of specified user
+    let audit = Audit::new("user.session_revoke", &client).actor(&user.id.0)
+        .target(id);
+    let result: Option<Record> = audit.query(db, "
+        let $deleted = (
+            DELETE login
+            WHERE id = type::thing('login', $sid)
+                AND user = type::thing('user', $uid)
+            RETURN BEFORE
+        );
+        let $audited = !!$deleted;
+        $deleted;
+    ").bind(("sid", session)).bind(("uid", id))
+        .await.take(2)
+        .context("error revoking session")?;
+
+    // return success or not found status
+    result.map(|_| Status::NoContent).ok_or(Status::NotFound.into())
+}
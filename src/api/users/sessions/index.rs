@@ -0,0 +1,64 @@
+//! User session listing route.
+
+use rocket::{get, http::Status, serde::json::Json};
+use surrealdb::sql::Duration;
+
+use crate::database::{Database, Take};
+use super::super::{
+    super::{
+        auth::sessions::components::SessionOut,
+        login::{Login, User},
+        permission::{Permission, UsersRead},
+        problem::{Context, Error},
+        Config,
+    },
+    target,
+};
+
+#[utoipa::path(
+    context_path = "/api/users",
+    responses((
+        status = 200, description = "List of active sessions",
+        body = Vec<SessionOut>,
+    ), (
+        status = 401, description = "Invalid login session",
+    ), (
+        status = 403, description = "Insufficient privileges",
+    ), (
+        status = 404, description = "User not found",
+    )),
+    security(("login" = [])),
+    tag = "users",
+)]
+
+/// GET /api/users/{id}/sessions
+///
+/// List active login sessions of a user by their ID. Requires the `users:read`
+/// permission and outranking the user except for listing sessions of the
+/// currently logged in user.
+#[get("/<id>/sessions")]
+pub async fn route(
+    user: Login<User>, db: &Database, config: &Config, id: &str,
+) -> Result<Json<Vec<SessionOut>>, Error> {
+    // only allow listing others' sessions with permission if outranking them
+    if user.id != id {
+        if !user.can(UsersRead::NAME) { return Err(Status::Forbidden.into()); }
+        target(&user, db, id).await?;
+    }
+
+    // fetch sessions from database
+    let sessions: Vec<SessionOut> = db.query("
+        SELECT
+            id, created, last_used, expires, ip, user_agent, impersonator,
+            id = type::thing('login', $sid) AS current
+        FROM login
+        WHERE user = type::thing('user', $uid) AND expires > time::now()
+            AND last_used > time::now() - $idle
+        ORDER BY last_used DESC
+    ").bind(("uid", id)).bind(("sid", &user.session.0))
+        .bind(("idle", Duration::from_secs(config.session_idle_timeout)))
+        .await.take(0)
+        .context("error retrieving sessions")?;
+
+    Ok(Json(sessions))
+}
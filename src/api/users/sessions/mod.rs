@@ -0,0 +1,5 @@
+//! User session routes.
+
+pub mod index;
+pub mod destroy;
+pub mod clear;
@@ -3,7 +3,13 @@
 use rocket::{get, http::Status, serde::json::Json};
 
 use crate::database::Database;
-use super::{super::login::{Admin, Login, User}, components::UserOut};
+use super::{
+    super::{
+        login::{Login, User}, permission::{Permission, UsersRead},
+        problem::{Context, Error},
+    },
+    components::UserOut,
+};
 
 #[utoipa::path(
     context_path = "/api/users",
@@ -19,20 +25,20 @@ use super::{super::login::{Admin, Login, User}, components::UserOut};
 
 /// GET /api/users/{id}
 ///
-/// Retrieve user by their ID. Requires admin privileges except for retrieving
-/// data of currently logged in user.
+/// Retrieve user by their ID. Requires the `users:read` permission except for
+/// retrieving data of currently logged in user.
 #[get("/<id>")]
 pub async fn route(
     user: Login<User>, db: &Database, id: &str
-) -> Result<Json<UserOut>, Status> {
-    // only allow reading own info if not admin
-    let authorized = user.is(Admin) || user.id == id;
-    if !authorized { return Err(Status::Forbidden); }
+) -> Result<Json<UserOut>, Error> {
+    // only allow reading own info without permission
+    let authorized = user.can(UsersRead::NAME) || user.id == id;
+    if !authorized { return Err(Status::Forbidden.into()); }
 
     // fetch user from database
     let user: Option<UserOut> = db.select(("user", id)).await
-        .expect("error retrieving user");
+        .context("error retrieving user")?;
 
     // return user or not found status
-    user.map(Json).ok_or(Status::NotFound)
+    user.map(Json).ok_or(Status::NotFound.into())
 }
@@ -0,0 +1,76 @@
+//! User suspension route.
+
+use rocket::{http::Status, post, serde::json::Json};
+use validator::Validate;
+
+use crate::{database::{Database, Take}, mail::Mail};
+use super::{
+    super::{
+        audit::Audit, client::Client, login::{Login, Perm},
+        permission::UsersSuspend, problem::{Context, Error},
+    },
+    components::{SuspendIn, UserOut},
+    target,
+};
+
+#[utoipa::path(
+    context_path = "/api/users",
+    request_body = SuspendIn,
+    responses(
+        (status = 200, description = "Suspended user", body = UserOut),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Insufficient privileges"),
+        (status = 404, description = "User not found"),
+        (status = 422, description = "Invalid suspension data"),
+    ),
+    security(("login" = [])),
+    tag = "users",
+)]
+
+/// POST /api/users/{id}/suspend
+///
+/// Suspend user by their ID with a reason and optionally until a given date,
+/// revoking all their login sessions and notifying them by email. Suspended
+/// users cannot log in. Requires the `users:suspend` permission and outranking
+/// the user by the permissions of their role. The suspension is recorded in
+/// the audit log.
+#[post("/<id>/suspend", data = "<data>")]
+pub async fn route(
+    user: Login<Perm<UsersSuspend>>, db: &Database, mail: &Mail,
+    client: Client, id: &str, data: Json<SuspendIn>,
+) -> Result<Json<UserOut>, Error> {
+    // validate input
+    data.validate()?;
+
+    // check privileges on user to be suspended
+    let target = target(&user, db, id).await?;
+
+    // query database to suspend user and revoke their sessions
+    let audit = Audit::new("user.suspend", &client).actor(&user.id.0)
+        .target(id);
+    let result: Option<UserOut> = audit.query(db, "
+        let $user = type::thing('user', $uid);
+        let $end = if $until then <datetime> $until end;
+        let $before = { disabled: $user.disabled };
+
+        DELETE login WHERE user = $user OR impersonator = $user;
+        DELETE login_challenge WHERE user = $user;
+        UPDATE ONLY $user SET disabled = {
+            reason: $reason, since: time::now(), until: $end,
+        } RETURN AFTER;
+
+        let $diff = { before: $before, after: { disabled: $user.disabled } };
+    ").bind(("uid", id)).bind(("reason", &data.reason))
+        .bind(("until", &data.until))
+        .await.take(5)
+        .context("error executing suspension query")?;
+    let result = result.ok_or(Status::NotFound)?;
+
+    // spawn job for notifying user
+    let until = data.until.as_deref().unwrap_or("further notice");
+    mail.spawn(&target.email, "account-suspended", &[
+        ("name", &target.name), ("reason", &data.reason), ("until", until),
+    ]);
+
+    Ok(Json(result))
+}
@@ -0,0 +1,60 @@
+//! User two-factor reset route.
+
+use rocket::{delete, http::Status};
+
+use crate::database::{Database, Take};
+use super::{
+    super::{
+        audit::Audit, client::Client, login::{Login, Perm},
+        permission::TwoFactorReset, problem::{Context, Error},
+    },
+    target,
+};
+
+#[utoipa::path(
+    context_path = "/api/users",
+    responses(
+        (status = 204, description = "Two-factor authentication disabled"),
+        (status = 404, description = "User not found"),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Insufficient privileges"),
+    ),
+    security(("login" = [])),
+    tag = "users",
+)]
+
+/// DELETE /api/users/{id}/2fa
+///
+/// Force-disable two-factor authentication of a user by their ID, deleting
+/// their secret, recovery codes, and open login challenges. Requires the
+/// `users:2fa_reset` permission and outranking the user by the permissions of
+/// their role, and is recorded in the audit log.
+#[delete("/<id>/2fa")]
+pub async fn route(
+    user: Login<Perm<TwoFactorReset>>, db: &Database, client: Client,
+    id: &str,
+) -> Result<Status, Error> {
+    // check privileges on user to be reset
+    target(&user, db, id).await?;
+
+    // query database to remove secret, recovery codes, and challenges
+    let audit = Audit::new("user.2fa_reset", &client).actor(&user.id.0)
+        .target(id);
+    let result: Option<bool> = audit.query(db, "
+        let $user = type::thing('user', $uid);
+        let $audited = !!$user.id;
+
+        if $audited {
+            UPDATE $user SET
+                totp_secret = NONE, totp_pending = NONE, totp_step = NONE;
+            DELETE recovery_code WHERE user = $user;
+            DELETE login_challenge WHERE user = $user;
+            true;
+        };
+    ").bind(("uid", id))
+        .await.take(2)
+        .context("error executing two-factor reset query")?;
+
+    // return success or not found status
+    result.map(|_| Status::NoContent).ok_or(Status::NotFound.into())
+}
@@ -0,0 +1,62 @@
+//! User reactivation route.
+
+use rocket::{http::Status, post, serde::json::Json};
+
+use crate::{database::{Database, Take}, mail::Mail};
+use super::{
+    super::{
+        audit::Audit, client::Client, login::{Login, Perm},
+        permission::UsersSuspend, problem::{Context, Error},
+    },
+    components::UserOut,
+    target,
+};
+
+#[utoipa::path(
+    context_path = "/api/users",
+    responses(
+        (status = 200, description = "Reactivated user", body = UserOut),
+        (status = 401, description = "Invalid login session"),
+        (status = 403, description = "Insufficient privileges"),
+        (status = 404, description = "User not found"),
+    ),
+    security(("login" = [])),
+    tag = "users",
+)]
+
+/// POST /api/users/{id}/unsuspend
+///
+/// Lift suspension of user by their ID and notify them by email if they were
+/// suspended. Requires the `users:suspend` permission and outranking the user
+/// by the permissions of their role. The reactivation is recorded in the audit
+/// log.
+#[post("/<id>/unsuspend")]
+pub async fn route(
+    user: Login<Perm<UsersSuspend>>, db: &Database, mail: &Mail,
+    client: Client, id: &str,
+) -> Result<Json<UserOut>, Error> {
+    // check privileges on user to be reactivated
+    let target = target(&user, db, id).await?;
+
+    // query database to lift suspension
+    let audit = Audit::new("user.unsuspend", &client).actor(&user.id.0)
+        .target(id);
+    let result: Option<UserOut> = audit.query(db, "
+        let $user = type::thing('user', $uid);
+        let $diff = { before: { disabled: $user.disabled }, after: {} };
+
+        UPDATE ONLY $user SET disabled = NONE RETURN AFTER;
+    ").bind(("uid", id))
+        .await.take(2)
+        .context("error executing reactivation query")?;
+    let result = result.ok_or(Status::NotFound)?;
+
+    // spawn job for notifying user if they were suspended
+    if target.disabled.is_some() {
+        mail.spawn(&target.email, "account-reactivated", &[
+            ("name", &target.name),
+        ]);
+    }
+
+    Ok(Json(result))
+}
@@ -3,8 +3,15 @@
 use rocket::{http::Status, patch, serde::json::Json};
 use validator::Validate;
 
-use crate::database::Database;
-use super::{super::login::{Login, Owner, User}, components::{UserIn, UserOut}};
+use crate::database::{Database, Take};
+use super::{
+    super::{
+        audit::Audit, client::Client, login::{Login, User},
+        permission::{Permission, RolesAssign, UsersUpdate},
+        problem::{Context, Error}, roles::assignable,
+    },
+    components::{UserIn, UserOut}, target,
+};
 
 #[utoipa::path(
     context_path = "/api/users",
@@ -13,6 +20,8 @@ use super::{super::login::{Login, Owner, User}, components::{UserIn, UserOut}};
         (status = 200, description = "Updated user", body = UserOut),
         (status = 401, description = "Invalid login session"),
         (status = 403, description = "Insufficient privileges"),
+        (status = 404, description = "User not found"),
+        (status = 422, description = "Invalid user data"),
     ),
     security(("login" = [])),
     tag = "users",
@@ -20,29 +29,53 @@ use super::{super::login::{Login, Owner, User}, components::{UserIn, UserOut}};
 
 /// PATCH /api/users/{id}
 ///
-/// Update user data by their ID. Requires owner privileges except for updating
-/// currently logged in user. The role field can only be changed with owner
-/// permissions and only accepts "user" or "admin".
+/// Update user data by their ID. Requires the `users:update` permission and
+/// outranking the user except for updating currently logged in user. The role
+/// field can only be changed for other users with the `roles:assign`
+/// permission and only accepts roles outranked by the logged in user.
+///
+/// The update is recorded in the audit log with the previous and new values.
 #[patch("/<id>", data = "<data>")]
 pub async fn route(
-    user: Login<User>, db: &Database, id: &str, data: Json<UserIn>,
-) -> Result<Json<UserOut>, Status> {
+    user: Login<User>, db: &Database, client: Client, id: &str,
+    data: Json<UserIn>,
+) -> Result<Json<UserOut>, Error> {
     // validate input
-    data.validate().map_err(|_| Status::UnprocessableEntity)?;
+    data.validate()?;
 
-    // only allow updating own info if not owner
-    (user.is(Owner) || user.id == id)
+    // only allow updating own info without permission
+    (user.can(UsersUpdate::NAME) || user.id == id)
         .then_some(()).ok_or(Status::Forbidden)?;
-
-    // restrict updating role to owner
-    (user.is(Owner) || data.role.is_none())
+    (user.can(RolesAssign::NAME) || data.role.is_none())
         .then_some(()).ok_or(Status::Forbidden)?;
 
-    // query database to update user
-    let users: Option<UserOut> = db.update(("user", id))
-        .merge(data.into_inner())
-        .await.expect("error updating user");
+    // check privileges on other users and on role to be assigned
+    if user.id != id || data.role.is_some() {
+        target(&user, db, id).await?;
+    }
+    if let Some(role) = &data.role {
+        assignable(&user, db, role).await?;
+    }
+
+    // query database to update user and record changed values
+    let audit = Audit::new("user.update", &client).actor(&user.id.0).target(id);
+    let users: Option<UserOut> = audit.query(db, "
+        let $user = type::thing('user', $uid);
+        let $before = (SELECT name, role FROM ONLY $user);
+        let $audited = $before != NONE;
+
+        let $updated = if $audited then
+            (UPDATE ONLY $user MERGE $data RETURN AFTER)
+        end;
+        let $diff = if $audited then {
+            \"before\": $before,
+            \"after\": (SELECT name, role FROM ONLY $user),
+        } end;
+        $updated;
+    ").bind(("uid", id)).bind(("data", data.into_inner()))
+        .await.take(5)
+        .context("error updating user")?;
 
     // return users or not found status
-    users.map(Json).ok_or(Status::NotFound)
+    users.map(Json).ok_or(Status::NotFound.into())
 }
@@ -3,21 +3,42 @@
 use rocket::{
     figment::{self, providers::{Env, Serialized}},
     http::uncased::{Uncased, UncasedStr},
-    serde::json::json,
 };
 use serde::Deserialize;
 
 /// Load configuration, add defaults, add environment values, and parse it
-pub fn load() -> Result<Config, figment::Error> {
+pub fn load() -> Result<Config, Box<figment::Error>> {
     rocket::Config::figment()
         .join(Serialized::default("database.namespace", "default"))
         .join(Serialized::default("database.database", "default"))
         .join(Serialized::default("mail.pool_size", 1))
-        .join(Serialized::default("api", json!({})))
+        .join(Serialized::default("api.owner_bootstrap", false))
+        .join(Serialized::default("api.public_url", "http://localhost:8000"))
+        .join(Serialized::default("api.session_lifetime", 30 * 24 * 60 * 60))
+        .join(Serialized::default("api.session_idle_timeout", 7 * 24 * 60 * 60))
+        .join(Serialized::default("api.impersonation_lifetime", 15 * 60))
+        .join(Serialized::default("api.cookie_sessions", false))
+        .join(Serialized::default("api.totp_issuer", "Backend"))
+        .join(Serialized::default("api.deletion_grace_days", 30))
+        .join(Serialized::default("api.deletion_purge_interval", 60 * 60))
+        .join(Serialized::default("api.audit_retention_days", 365))
+        .join(Serialized::default("api.registration", "open"))
+        .join(Serialized::default("api.registration_domains", Vec::<String>::new()))
+        .join(Serialized::default("api.login_max_failures", 5))
+        .join(Serialized::default("api.login_max_ip_failures", 20))
+        .join(Serialized::default("api.login_failure_window", 60 * 60))
+        .join(Serialized::default("api.login_lockout", 60))
+        .join(Serialized::default("api.login_lockout_max", 60 * 60))
+        .join(Serialized::default("api.pow_register_difficulty", 16))
+        .join(Serialized::default("api.pow_password_reset_difficulty", 16))
+        .join(Serialized::default("api.pow_body_limit", 512))
+        .join(Serialized::default("api.pow_adaptive", false))
+        .join(Serialized::default("api.pow_adaptive_rate", 60))
+        .join(Serialized::default("api.pow_max_difficulty", 24))
         .join(Serialized::default("files.path", "/usr/local/share/backend"))
         .join(Serialized::default("openapi.enable", false))
         .merge(Env::raw().map(convert_name).profile("global"))
-        .extract()
+        .extract().map_err(Box::new)
 }
 
 /// Replace first underscore with a dot.
@@ -57,6 +78,44 @@ pub struct MailConfig {
 #[derive(Debug, Deserialize)]
 pub struct APIConfig {
     pub owner: Option<String>,
+    pub owner_bootstrap: bool,
+    pub public_url: String,
+    pub session_lifetime: u64,
+    pub session_idle_timeout: u64,
+    pub impersonation_lifetime: u64,
+    pub cookie_sessions: bool,
+    pub totp_issuer: String,
+    pub deletion_grace_days: u64,
+    pub deletion_purge_interval: u64,
+    pub audit_retention_days: u64,
+    pub registration: RegistrationPolicy,
+    pub registration_domains: Vec<String>,
+    pub login_max_failures: u64,
+    pub login_max_ip_failures: u64,
+    pub login_failure_window: u64,
+    pub login_lockout: u64,
+    pub login_lockout_max: u64,
+    pub ip_header: Option<String>,
+    pub pow_register_difficulty: u32,
+    pub pow_password_reset_difficulty: u32,
+    pub pow_body_limit: u64,
+    pub pow_adaptive: bool,
+    pub pow_adaptive_rate: u64,
+    pub pow_max_difficulty: u32,
+}
+
+/// Registration policy type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RegistrationPolicy {
+    /// Anyone may register.
+    Open,
+    /// Only email addresses of allowed domains may register.
+    Domains,
+    /// Registration requires an invite code created by an admin.
+    Invite,
+    /// Nobody may register.
+    Closed,
 }
 
 /// Files config type.
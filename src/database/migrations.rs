@@ -53,7 +53,7 @@ pub enum Error {
     DirectoryTree,
 
     #[error("database error")]
-    Database { #[from] source: surrealdb::Error },
+    Database { #[from] source: Box<surrealdb::Error> },
 
     #[error("database expected newer program version")]
     Outdated,
@@ -65,6 +65,12 @@ pub enum Error {
     HashMismatch { name: String },
 }
 
+impl From<surrealdb::Error> for Error {
+    fn from(source: surrealdb::Error) -> Self {
+        Box::new(source).into()
+    }
+}
+
 /// Apply all open migrations to the specified database.
 pub async fn apply(db: &Surreal<Any>) -> Result<(), Error> {
     // load current migrations and database state
@@ -93,7 +99,7 @@ pub async fn apply(db: &Surreal<Any>) -> Result<(), Error> {
     }
 
     // apply new migrations
-    for mig in migrations[state.len()..].into_iter() {
+    for mig in migrations[state.len()..].iter() {
         info!("Applying migration \"{}\"", mig.name);
 
         db.query(&mig.content).query("
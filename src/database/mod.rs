@@ -1,11 +1,11 @@
 //! SurrealDB database integration.
 
 use rocket::{error, fairing::AdHoc, State};
-use serde::Deserialize;
+use serde::{de::DeserializeOwned, Deserialize};
 use surrealdb::{
     engine::any::{self, Any},
-    opt::auth::Root,
-    Surreal,
+    opt::{auth::Root, QueryResult},
+    Response, Surreal,
 };
 
 mod migrations;
@@ -22,6 +22,30 @@ pub type Database = State<Surreal<Any>>;
 #[derive(Deserialize)]
 pub struct Record {}
 
+/// Extension trait for taking statement results from awaited queries, with
+/// boxed errors to keep results small.
+pub trait Take {
+    /// Take result of statement by its index from query response.
+    fn take<R: DeserializeOwned>(
+        self, index: impl QueryResult<R>,
+    ) -> Result<R, Box<surrealdb::Error>>;
+
+    /// Check query response for errors of any statement.
+    fn check(self) -> Result<Response, Box<surrealdb::Error>>;
+}
+
+impl Take for Result<Response, surrealdb::Error> {
+    fn take<R: DeserializeOwned>(
+        self, index: impl QueryResult<R>,
+    ) -> Result<R, Box<surrealdb::Error>> {
+        Ok(self?.take(index)?)
+    }
+
+    fn check(self) -> Result<Response, Box<surrealdb::Error>> {
+        Ok(self?.check()?)
+    }
+}
+
 /// Create and mount database to the rocket instance.
 pub fn mount(config: DatabaseConfig) -> AdHoc {
     AdHoc::try_on_ignite("Mount SurrealDB", |rocket| async move {
@@ -6,12 +6,17 @@ use rocket::{
     Data, Request, Route,
 };
 use utoipa::{
-    openapi::{self, security::{ApiKey, ApiKeyValue, SecurityScheme}},
+    openapi::{
+        self, security::{ApiKey, ApiKeyValue, SecurityScheme},
+        Content, PathItemType, Ref, RefOr,
+    },
     Modify, OpenApi,
 };
 use utoipa_rapidoc::RapiDoc;
 
-use crate::{api, database};
+use crate::{
+    api, config::{APIConfig, RegistrationPolicy}, database,
+};
 
 /// URI where OpenAPI JSON is being served.
 pub const OPENAPI_URI: &str = "/api-docs/openapi.json";
@@ -19,7 +24,14 @@ pub const OPENAPI_URI: &str = "/api-docs/openapi.json";
 /// Mount OpenAPI and RapiDoc documentation routes.
 pub fn mount() -> AdHoc {
     AdHoc::try_on_ignite("RapiDoc Documentation", |rocket| async {
-        let json = match ApiDoc::openapi().to_json() {
+        // describe registration policy of mounted API
+        let mut openapi = ApiDoc::openapi();
+        match rocket.state::<APIConfig>() {
+            Some(config) => registration_policy(&mut openapi, config),
+            None => { error!("Error getting API config"); return Err(rocket); }
+        }
+
+        let json = match openapi.to_json() {
             Ok(json) => json,
             Err(_) => { error!("invalid OpenAPI JSON"); return Err(rocket); }
         };
@@ -41,36 +53,161 @@ pub fn mount() -> AdHoc {
             "Interactive API documentation.",
     ),
     paths(
+        api::pow::challenge::route,
         api::auth::register::route, api::auth::confirm::route,
+        api::auth::accept::route, api::auth::restore::route,
         api::auth::login::route, api::auth::logout::route,
+        api::auth::logout_all::route,
+        api::auth::sessions::index::route, api::auth::sessions::destroy::route,
+        api::auth::keys::index::route, api::auth::keys::create::route,
+        api::auth::keys::destroy::route,
         api::auth::password::reset::route, api::auth::password::confirm::route,
-        api::users::index::route, api::users::show::route,
+        api::auth::password::change::route,
+        api::auth::email::change::route, api::auth::email::confirm::route,
+        api::auth::two_factor::setup::route,
+        api::auth::two_factor::enable::route,
+        api::auth::two_factor::disable::route,
+        api::auth::two_factor::verify::route,
+        api::users::index::route, api::users::create::route,
+        api::users::show::route,
         api::users::update::route, api::users::destroy::route,
+        api::users::restore::route,
+        api::users::sessions::index::route,
+        api::users::sessions::destroy::route,
+        api::users::sessions::clear::route, api::users::two_factor::route,
+        api::users::suspend::route, api::users::unsuspend::route,
+        api::users::export::route, api::users::mail_export::route,
+        api::users::download::route, api::users::impersonate::route,
+        api::invites::index::route, api::invites::create::route,
+        api::invites::destroy::route,
+        api::roles::index::route, api::roles::create::route,
+        api::roles::update::route, api::roles::destroy::route,
+        api::organizations::index::route, api::organizations::create::route,
+        api::organizations::show::route, api::organizations::update::route,
+        api::organizations::destroy::route, api::organizations::join::route,
+        api::organizations::members::index::route,
+        api::organizations::members::create::route,
+        api::organizations::members::update::route,
+        api::organizations::members::destroy::route,
+        api::audit::index::route,
+        api::ownership::transfer::route, api::ownership::accept::route,
     ),
     components(schemas(
-        database::Id<String>,
+        database::Id<String>, api::problem::Problem,
+        api::pow::components::PowChallengeOut,
         api::auth::components::RegisterIn, api::auth::components::ConfirmIn,
+        api::auth::components::AcceptIn, api::auth::components::RestoreIn,
         api::auth::components::LoginIn, api::auth::components::LoginOut,
+        api::auth::components::ChallengeOut,
+        api::auth::sessions::components::SessionOut,
+        api::auth::keys::components::KeyIn,
+        api::auth::keys::components::KeyOut,
+        api::auth::keys::components::KeyCreatedOut,
         api::auth::password::components::ResetIn,
         api::auth::password::components::PasswordConfirmIn,
+        api::auth::password::components::PasswordChangeIn,
+        api::auth::email::components::EmailChangeIn,
+        api::auth::email::components::EmailConfirmIn,
+        api::auth::two_factor::components::TwoFactorSetupOut,
+        api::auth::two_factor::components::TwoFactorCodeIn,
+        api::auth::two_factor::components::RecoveryCodesOut,
+        api::auth::two_factor::components::TwoFactorVerifyIn,
+        api::users::components::UserCreateIn,
         api::users::components::UserIn, api::users::components::UserOut,
+        api::users::components::InvitationOut,
+        api::users::components::SuspendIn,
+        api::users::components::DisabledOut,
+        api::invites::components::InviteIn,
+        api::invites::components::InviteOut,
+        api::invites::components::InviteCodeOut,
+        api::roles::components::RoleIn, api::roles::components::RoleUpdateIn,
+        api::roles::components::RoleOut,
+        api::organizations::components::OrganizationIn,
+        api::organizations::components::OrganizationOut,
+        api::organizations::components::JoinIn,
+        api::organizations::components::MemberInviteIn,
+        api::organizations::components::MemberIn,
+        api::organizations::components::MemberOut,
+        api::organizations::components::MemberInvitationOut,
+        api::audit::components::AuditEventOut,
+        api::ownership::components::TransferIn,
+        api::ownership::components::TransferOut,
+        api::ownership::components::AcceptIn,
     )),
-    modifiers(&LoginToken),
+    modifiers(&LoginToken, &PaginatedSchemas, &ProblemResponses),
 )]
 struct ApiDoc;
 
+/// Append configured registration policy to registration route description.
+fn registration_policy(openapi: &mut openapi::OpenApi, config: &APIConfig) {
+    let policy = match config.registration {
+        RegistrationPolicy::Open => "open to everyone".into(),
+        RegistrationPolicy::Domains => format!(
+            "restricted to email addresses of the domains {}",
+            config.registration_domains.join(", "),
+        ),
+        RegistrationPolicy::Invite =>
+            "restricted to holders of an invite code".into(),
+        RegistrationPolicy::Closed => "closed".into(),
+    };
+
+    let operation = openapi.paths.paths.get_mut("/api/auth/register")
+        .and_then(|item| item.operations.get_mut(&PathItemType::Post));
+    if let Some(operation) = operation {
+        let description = operation.description.get_or_insert_with(String::new);
+        description.push_str(&format!(
+            "\n\n**Registration on this server is {policy}.**"
+        ));
+    }
+}
+
 /// Login token modifier.
 struct LoginToken;
 impl Modify for LoginToken {
     fn modify(&self, openapi: &mut openapi::OpenApi) {
         let key = ApiKey::Header(ApiKeyValue::with_description(
-            "authorization", "API token prefixed with \"apikey \"",
+            "authorization",
+            "Session token or API key prefixed with \"apikey \"",
         ));
         openapi.components.as_mut().expect("error extracting components")
             .add_security_scheme("login", SecurityScheme::ApiKey(key));
     }
 }
 
+/// Paginated response envelope modifier for schemas of listed items.
+struct PaginatedSchemas;
+impl Modify for PaginatedSchemas {
+    fn modify(&self, openapi: &mut openapi::OpenApi) {
+        let components = openapi.components.as_mut()
+            .expect("error extracting components");
+        for items in ["UserOut", "MemberOut", "AuditEventOut"] {
+            components.schemas.insert(
+                format!("Paginated{items}"), api::page::schema(items).into(),
+            );
+        }
+    }
+}
+
+/// Problem details modifier for error responses without content.
+struct ProblemResponses;
+impl Modify for ProblemResponses {
+    fn modify(&self, openapi: &mut openapi::OpenApi) {
+        let operations = openapi.paths.paths.values_mut()
+            .flat_map(|item| item.operations.values_mut());
+        for operation in operations {
+            for (code, response) in operation.responses.responses.iter_mut() {
+                let RefOr::T(response) = response else { continue };
+                if code.starts_with(['4', '5']) && response.content.is_empty() {
+                    response.content.insert(
+                        "application/problem+json".into(),
+                        Content::new(Ref::from_schema_name("Problem")),
+                    );
+                }
+            }
+        }
+    }
+}
+
 /// Raw JSON handler for serving rendered OpenAPI documentation.
 #[derive(Clone)]
 struct RawJsonHandler(String);
@@ -12,16 +12,20 @@ mod config;
 mod database;
 pub mod mail;
 mod api;
+pub use api::{export, purge::purge};
 mod doc;
 mod files;
+pub mod totp;
 
 /// Build the rocket instance ready to be ignited and launched.
 pub fn rocket() -> Rocket<Build> {
     // load config and panic on errors
     let config = match config::load() {
         Ok(config) => config,
-        Err(figment::Error { path, kind, .. }) =>
-            panic!("Configuration error: {} in {}", kind, path.join(".")),
+        Err(err) => {
+            let figment::Error { path, kind, .. } = *err;
+            panic!("Configuration error: {} in {}", kind, path.join("."))
+        },
     };
     let files_path = PathBuf::from(config.files.path);
 
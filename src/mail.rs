@@ -62,6 +62,7 @@ pub struct Mailer {
 
 /// Transport enum for production SMTP or dummy queue.
 #[derive(Clone)]
+#[allow(clippy::upper_case_acronyms)]
 enum Transport {
     SMTP(AsyncSmtpTransport::<Tokio1Executor>),
     Dummy(DummyTransport),
@@ -130,6 +131,28 @@ impl Mailer {
         Ok(())
     }
 
+    /// Render template and send it in a background job, logging failures.
+    pub fn spawn(&self, to: &str, name: &str, vars: &[(&str, &str)]) {
+        let mailer = self.clone();
+        let (to, name) = (to.to_string(), name.to_string());
+        let vars: Vec<(String, String)> = vars.iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+
+        tokio::spawn(async move {
+            let vars: Vec<(&str, &str)> = vars.iter()
+                .map(|(key, value)| (key.as_str(), value.as_str()))
+                .collect();
+            let result = match mailer.template(&name, &vars).await {
+                Ok(email) => mailer.send(&to, email).await,
+                Err(err) => Err(err),
+            };
+            if let Err(err) = result {
+                error!("Error sending \"{name}\" email: {err:?}");
+            }
+        });
+    }
+
     /// Load template and substitute variables.
     pub async fn template(
         &self, name: &str, vars: &[(&str, &str)]
@@ -152,6 +175,7 @@ impl Mailer {
     }
 
     /// Receive next message if using dummy transport.
+    #[allow(clippy::result_unit_err)]
     pub fn receive_dummy(&self) -> Result<Message, ()> {
         match &self.transport {
             Transport::Dummy(tpt) => Ok(tpt.receive()),
@@ -1,7 +1,7 @@
 use backend_template::rocket;
 
 #[rocket::main]
-async fn main() -> Result<(), rocket::Error> {
+async fn main() -> Result<(), Box<rocket::Error>> {
     rocket().ignite().await?.launch().await?;
     Ok(())
 }
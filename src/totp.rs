@@ -0,0 +1,116 @@
+//! Time-based one-time passwords as specified by RFC 6238.
+
+use hmac::{Hmac, Mac};
+use rand::{distributions::Alphanumeric, Rng, RngCore};
+use rocket::http::RawStr;
+use sha1::Sha1;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Duration of a single time step in seconds.
+pub const PERIOD: u64 = 30;
+
+/// Number of digits of generated codes.
+pub const DIGITS: u32 = 6;
+
+/// Base32 alphabet as specified by RFC 4648.
+const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Generate random 160 bit secret encoded as base32.
+pub fn secret() -> String {
+    let mut bytes = [0u8; 20];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    encode(&bytes)
+}
+
+/// Generate random alphanumeric recovery codes.
+pub fn recovery_codes(count: usize) -> Vec<String> {
+    let mut rng = rand::thread_rng();
+    (0..count).map(|_| {
+        (&mut rng).sample_iter(Alphanumeric).take(10)
+            .map(|c| (c as char).to_ascii_lowercase()).collect()
+    }).collect()
+}
+
+/// Assemble key URI for provisioning authenticator apps.
+pub fn uri(secret: &str, issuer: &str, account: &str) -> String {
+    let issuer = RawStr::new(issuer).percent_encode();
+    let account = RawStr::new(account).percent_encode();
+    format!(
+        "otpauth://totp/{issuer}:{account}?secret={secret}&issuer={issuer}\
+            &algorithm=SHA1&digits={DIGITS}&period={PERIOD}"
+    )
+}
+
+/// Get current UNIX time in seconds.
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Calculate code for base32 encoded secret at the specified UNIX time.
+pub fn code(secret: &str, time: u64) -> Option<String> {
+    Some(code_at_step(&decode(secret)?, time / PERIOD))
+}
+
+/// Verify code for base32 encoded secret at the specified UNIX time, allowing
+/// one step of clock drift, and return the matching time step.
+pub fn verify(secret: &str, code: &str, time: u64) -> Option<u64> {
+    let key = decode(secret)?;
+    let step = time / PERIOD;
+    [step.saturating_sub(1), step, step + 1].into_iter()
+        .find(|step| code_at_step(&key, *step) == code)
+}
+
+/// Calculate code for key at time step using dynamic truncation.
+fn code_at_step(key: &[u8], step: u64) -> String {
+    // calculate HMAC-SHA1 of the big-endian time step
+    let mut mac = Hmac::<Sha1>::new_from_slice(key)
+        .expect("HMAC accepts keys of any length");
+    mac.update(&step.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+
+    // extract 31 bit integer at offset given by last nibble
+    let offset = (hash[19] & 0xf) as usize;
+    let num = u32::from_be_bytes([
+        hash[offset] & 0x7f, hash[offset + 1],
+        hash[offset + 2], hash[offset + 3],
+    ]);
+
+    // format as zero-padded decimal code
+    format!("{:0width$}", num % 10u32.pow(DIGITS), width = DIGITS as usize)
+}
+
+/// Encode bytes as unpadded base32.
+fn encode(bytes: &[u8]) -> String {
+    let mut result = String::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for byte in bytes {
+        buffer = (buffer << 8) | *byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            result.push(ALPHABET[(buffer >> bits) as usize & 31] as char);
+        }
+    }
+    if bits > 0 {
+        result.push(ALPHABET[(buffer << (5 - bits)) as usize & 31] as char);
+    }
+    result
+}
+
+/// Decode unpadded base32 ignoring case.
+fn decode(text: &str) -> Option<Vec<u8>> {
+    let mut result = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in text.trim_end_matches('=').bytes() {
+        let value = ALPHABET.iter()
+            .position(|a| *a == c.to_ascii_uppercase())? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            result.push((buffer >> bits) as u8);
+        }
+    }
+    Some(result)
+}
@@ -0,0 +1,60 @@
+// Proof of work solver for request bodies guarded by the backend.
+//
+// The request body is serialized as JSON with the challenge added and a
+// trailing "nonce" field appended. The SHA-512 hash of the UTF-8 encoded body
+// needs to begin with as many zero bits as the challenge's difficulty states.
+// The solved body has to be sent exactly as returned, since the server hashes
+// the raw bytes.
+
+export const VERSION = 1;
+
+// Fetch challenge for a scope like "register" or "password_reset".
+export async function fetchChallenge(scope, { base = "" } = {}) {
+  const params = new URLSearchParams({ scope });
+  const resp = await fetch(`${base}/api/pow/challenge?${params}`);
+  if (!resp.ok) {
+    throw new Error(`error fetching challenge: ${resp.status}`);
+  }
+  return resp.json();
+}
+
+// Serialize body with challenge and return the prefix the nonce is appended to.
+export function prefix(body, challenge) {
+  const { nonce, ...rest } = body;
+  const json = JSON.stringify({ ...rest, challenge });
+  return json.slice(0, -1) + ',"nonce":';
+}
+
+// Solve body for a challenge in a web worker and return the serialized body.
+export function solveChallenge(body, { challenge, difficulty }, { signal } = {}) {
+  const worker = new Worker(new URL("./worker.js", import.meta.url), {
+    type: "module",
+  });
+
+  return new Promise((resolve, reject) => {
+    const abort = () => {
+      worker.terminate();
+      reject(signal.reason ?? new Error("aborted"));
+    };
+    if (signal?.aborted) { return abort(); }
+    signal?.addEventListener("abort", abort, { once: true });
+
+    worker.onmessage = e => {
+      signal?.removeEventListener("abort", abort);
+      worker.terminate();
+      resolve(e.data);
+    };
+    worker.onerror = e => {
+      signal?.removeEventListener("abort", abort);
+      worker.terminate();
+      reject(e.error ?? new Error(e.message));
+    };
+    worker.postMessage({ prefix: prefix(body, challenge), difficulty });
+  });
+}
+
+// Fetch challenge for scope and solve body, returning the serialized body.
+export async function solve(body, scope, options = {}) {
+  const challenge = await fetchChallenge(scope, options);
+  return solveChallenge(body, challenge, options);
+}
@@ -0,0 +1,26 @@
+// Web worker searching for a nonce, see solver.js for the body format.
+
+const encoder = new TextEncoder();
+
+// Count leading zero bits of a hash.
+function zeros(hash) {
+  let count = 0;
+  for (const byte of new Uint8Array(hash)) {
+    if (byte !== 0) { return count + Math.clz32(byte) - 24; }
+    count += 8;
+  }
+  return count;
+}
+
+// Respond with the first serialized body whose hash satisfies the difficulty.
+self.onmessage = async e => {
+  const { prefix, difficulty } = e.data;
+  for (let nonce = 0; ; nonce++) {
+    const body = `${prefix}${nonce}}`;
+    const hash = await crypto.subtle.digest("SHA-512", encoder.encode(body));
+    if (zeros(hash) >= difficulty) {
+      self.postMessage(body);
+      return;
+    }
+  }
+};
@@ -0,0 +1,119 @@
+use rocket::{
+    http::{Header, Status}, local::blocking::{Client, LocalResponse},
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+mod common;
+
+#[test]
+fn test_api_keys() {
+    let client = common::client();
+    let login: LoginResponse = client.post("/api/auth/login").json(&json!({
+        "email": "owner@example.com", "password": "supersecret",
+    })).dispatch().into_json().unwrap();
+    let owner = header(&login.token);
+    let alice: LoginResponse =
+        common::register(&client, "Alice", "alice@example.com");
+
+    // try creating keys with invalid data and scopes not granted
+    for body in [
+        json!({ "name": "", "scopes": [] }),
+        json!({ "name": "CI", "scopes": ["users:fly"] }),
+        json!({
+            "name": "CI", "scopes": [], "expires": "2000-01-01T00:00:00Z",
+        }),
+    ] {
+        let resp = create(&client, &owner, body);
+        assert_eq!(resp.status(), Status::UnprocessableEntity);
+    }
+    let resp = create(&client, &header(&alice.token), json!({
+        "name": "CI", "scopes": ["users:list"],
+    }));
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    // create scoped key shown once
+    let resp = create(&client, &owner, json!({
+        "name": "CI", "scopes": ["users:list"],
+    }));
+    assert_eq!(resp.status(), Status::Created);
+    let key: KeyResponse = resp.into_json().unwrap();
+    assert!(key.token.starts_with("key_"));
+    let stored: Vec<String> =
+        common::query(&client, "SELECT VALUE token FROM api_key");
+    assert!(!stored.contains(&key.token));
+    let ci = header(&key.token);
+
+    // use granted scope, but neither other permissions nor own session
+    let resp = client.get("/api/users").header(ci.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.get("/api/invites").header(ci.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = create(&client, &ci, json!({ "name": "Other", "scopes": [] }));
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = client.post("/api/auth/password/change").header(ci.clone())
+        .json(&json!({
+            "current_password": "supersecret", "password": "newpassword",
+        })).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    // limit scopes within organizations as well
+    let resp = client.post("/api/organizations").header(owner.clone())
+        .json(&json!({ "name": "Acme" })).dispatch();
+    let org: Value = resp.into_json().unwrap();
+    let members = format!("/api/organizations/{}/members", org["id"]
+        .as_str().unwrap());
+    let resp = client.get(&members).header(ci.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = create(&client, &owner, json!({ "name": "Bot", "scopes": [] }));
+    let bot: KeyResponse = resp.into_json().unwrap();
+    let resp = client.get(&members).header(header(&bot.token)).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    // list keys without tokens
+    let resp = client.get("/api/auth/keys").header(owner.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let keys: Vec<Value> = resp.into_json().unwrap();
+    assert_eq!(keys.len(), 2);
+    assert_eq!(keys[1]["name"], "CI");
+    assert!(keys[1]["last_used"].is_string());
+    assert!(keys.iter().all(|key| key.get("token").is_none()));
+
+    // reject expired key
+    let _: Vec<Value> = common::query(&client, "
+        UPDATE api_key SET expires = time::now() - 1s WHERE name = 'Bot'
+    ");
+    let resp = client.get("/api/auth/sessions").header(header(&bot.token))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    // revoke key
+    let url = format!("/api/auth/keys/{}", key.id);
+    let resp = client.delete(&url).header(owner.clone()).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+    let resp = client.get("/api/users").header(ci).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let resp = client.delete(&url).header(owner).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+fn create<'c>(
+    client: &'c Client, header: &Header<'static>, body: Value,
+) -> LocalResponse<'c> {
+    client.post("/api/auth/keys").header(header.clone()).json(&body).dispatch()
+}
+
+fn header(token: &str) -> Header<'static> {
+    Header::new("Authorization", format!("apikey {token}"))
+}
+
+#[derive(Deserialize)]
+struct LoginResponse {
+    token: String,
+}
+
+#[derive(Deserialize)]
+struct KeyResponse {
+    id: String,
+    token: String,
+}
@@ -0,0 +1,209 @@
+use rocket::{
+    http::{Header, Status}, local::blocking::Client, tokio::runtime,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use surrealdb::{engine::any::Any, Surreal};
+
+mod common;
+
+#[test]
+fn test_audit() {
+    let client = common::client();
+    let login: LoginResponse = client.post("/api/auth/login")
+        .header(Header::new("User-Agent", "Audit Test"))
+        .json(&json!({
+            "email": "owner@example.com", "password": "supersecret",
+        })).dispatch().into_json().unwrap();
+    let owner = header(&login);
+    let alice: LoginResponse =
+        common::register(&client, "Alice", "alice@example.com");
+
+    // fail login with wrong password
+    let resp = client.post("/api/auth/login").json(&json!({
+        "email": "alice@example.com", "password": "wrongpassword",
+    })).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    // try reading audit log as regular user
+    let resp = client.get("/api/audit").header(header(&alice)).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    // promote, suspend, and delete user as owner
+    let resp = client.patch(format!("/api/users/{}", alice.id))
+        .header(owner.clone()).json(&json!({ "role": "admin" })).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.post(format!("/api/users/{}/suspend", alice.id))
+        .header(owner.clone()).json(&json!({ "reason": "Test" })).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.delete(format!("/api/users/{}", alice.id))
+        .header(owner.clone()).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+
+    // list all events, newest first
+    let page = events(&client, &owner, "");
+    let actions: Vec<_> = page.items.iter()
+        .map(|event| event["action"].as_str().unwrap()).collect();
+    assert_eq!(actions, [
+        "user.delete", "user.suspend", "user.update", "auth.login_failed",
+        "auth.register", "auth.login",
+    ]);
+    assert_eq!(page.items[5]["actor"], login.id);
+    assert_eq!(page.items[5]["user_agent"], "Audit Test");
+    assert_eq!(page.items[3]["target"], alice.id);
+    assert!(page.items[3].get("actor").is_none());
+
+    // filter by action and check diff of update
+    let page = events(&client, &owner, "action=user.update");
+    assert_eq!(page.total, 1);
+    let event = &page.items[0];
+    assert_eq!(event["actor"], login.id);
+    assert_eq!(event["target"], alice.id);
+    assert_eq!(event["diff"]["before"]["role"], "user");
+    assert_eq!(event["diff"]["after"]["role"], "admin");
+    let page = events(&client, &owner, "action=user.suspend");
+    assert_eq!(page.items[0]["diff"]["after"]["disabled"]["reason"], "Test");
+
+    // filter by actor and target, and paginate
+    let page = events(&client, &owner, &format!("actor={}", alice.id));
+    assert_eq!(page.total, 1);
+    let page =
+        events(&client, &owner, &format!("target={}&limit=2", alice.id));
+    assert_eq!((page.total, page.items.len()), (4, 2));
+    let page = events(&client, &owner, "since=2100-01-01T00:00:00Z");
+    assert_eq!(page.total, 0);
+    let resp = client.get("/api/audit?until=yesterday").header(owner.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::UnprocessableEntity);
+
+    // try altering recorded events
+    let db = client.rocket().state::<Surreal<Any>>().unwrap();
+    let runtime = runtime::Builder::new_current_thread()
+        .enable_all().build().unwrap();
+    let result = runtime.block_on(async {
+        db.query("UPDATE audit_event SET action = 'forged'").await?.check()
+    });
+    assert!(result.is_err());
+    let page = events(&client, &owner, "action=forged");
+    assert_eq!(page.total, 0);
+}
+
+#[test]
+fn test_audit_coverage() {
+    let client = common::client();
+    let login: LoginResponse = client.post("/api/auth/login").json(&json!({
+        "email": "owner@example.com", "password": "supersecret",
+    })).dispatch().into_json().unwrap();
+    let owner = header(&login);
+    let alice: LoginResponse =
+        common::register(&client, "Alice", "alice@example.com");
+
+    // create organization, invite user, change their role, and remove them
+    let resp = client.post("/api/organizations").header(owner.clone())
+        .json(&json!({ "name": "Acme" })).dispatch();
+    assert_eq!(resp.status(), Status::Created);
+    let org = resp.into_json::<Value>().unwrap()["id"].as_str().unwrap()
+        .to_string();
+    let resp = client.post(format!("/api/organizations/{org}/members"))
+        .header(owner.clone()).json(&json!({ "email": "alice@example.com" }))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Accepted);
+    let email = common::mailer(&client).receive_dummy().unwrap();
+    let content = String::from_utf8(email.formatted()).unwrap();
+    let token = content.split("\r\n")
+        .find(|l| l.starts_with("Your invitation token:")).unwrap()
+        .rsplit_once(" ").unwrap().1;
+    let resp = client.post("/api/organizations/join").header(header(&alice))
+        .json(&json!({ "token": token })).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp =
+        client.patch(format!("/api/organizations/{org}/members/{}", alice.id))
+            .header(owner.clone()).json(&json!({ "role": "org_admin" }))
+            .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp =
+        client.delete(format!("/api/organizations/{org}/members/{}", alice.id))
+            .header(owner.clone()).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+    let resp = client.delete(format!("/api/organizations/{org}"))
+        .header(owner.clone()).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+
+    // export user, set up 2FA, request email change, and revoke session
+    let resp = client.get(format!("/api/users/{}/export", alice.id))
+        .header(owner.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.post(format!("/api/users/{}/export", alice.id))
+        .header(owner.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Accepted);
+    let resp = client.post("/api/auth/2fa/setup").header(owner.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.post("/api/auth/email/change").header(owner.clone())
+        .json(&json!({ "email": "boss@example.com" })).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+    let resp = client.get("/api/auth/sessions").header(header(&alice))
+        .dispatch();
+    let session = resp.into_json::<Value>().unwrap()[0]["id"].as_str()
+        .unwrap().to_string();
+    let resp = client.delete(format!("/api/auth/sessions/{session}"))
+        .header(header(&alice)).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+
+    // check all changes being recorded with the organization in the diff
+    let page = events(&client, &owner, "limit=100");
+    let actions: Vec<_> = page.items.iter()
+        .map(|event| event["action"].as_str().unwrap()).collect();
+    assert_eq!(actions[..10], [
+        "auth.session_revoke", "auth.email_change_request", "auth.2fa_setup",
+        "user.export_mail", "user.export", "organization.delete",
+        "organization.member_remove", "organization.member_update",
+        "organization.member_invite", "organization.create",
+    ]);
+    assert_eq!(page.items[0]["actor"], alice.id);
+    assert_eq!(page.items[4]["target"], alice.id);
+    assert_eq!(page.items[7]["diff"]["before"]["role"], "user");
+    assert_eq!(page.items[7]["diff"]["after"]["role"], "org_admin");
+    for event in &page.items[5..10] {
+        assert_eq!(event["diff"]["organization"], org);
+    }
+}
+
+#[test]
+fn test_audit_retention() {
+    let client = common::client();
+    let _: Vec<Value> = common::query(&client, "
+        CREATE audit_event SET action = 'old', created = time::now() - 2d;
+        CREATE audit_event SET action = 'recent';
+    ");
+
+    // check old event being purged
+    common::purge_with(&client, 30, 1);
+    let actions: Vec<String> = common::query(&client, "
+        SELECT VALUE action FROM audit_event WHERE action IN ['old', 'recent']
+    ");
+    assert_eq!(actions, ["recent"]);
+}
+
+fn events(client: &Client, header: &Header<'static>, query: &str) -> Page {
+    let resp = client.get(format!("/api/audit?{query}"))
+        .header(header.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    resp.into_json().unwrap()
+}
+
+fn header(login: &LoginResponse) -> Header<'static> {
+    Header::new("Authorization", format!("apikey {}", login.token))
+}
+
+#[derive(Deserialize)]
+struct LoginResponse {
+    id: String,
+    token: String,
+}
+
+#[derive(Deserialize)]
+struct Page {
+    items: Vec<Value>,
+    total: u64,
+}
@@ -1,6 +1,7 @@
-use rocket::http::{Header, Status};
+use rocket::{http::{Header, Status}, local::blocking::Client};
 use serde::Deserialize;
 use serde_json::json;
+use sha2::{Digest, Sha256};
 
 mod common;
 
@@ -9,30 +10,32 @@ fn test_auth() {
     let client = common::client();
 
     // register with invalid email
-    let resp = client.post("/api/auth/register").json(&json!({
+    let body = common::solve(&client, "register", json!({
         "name": "Alice",
         "email": "alice",
         "password": "supersecret",
-        "nonce": 3730,
-    })).dispatch();
+    }));
+    let resp = client.post("/api/auth/register").json(&body).dispatch();
     assert_eq!(resp.status(), Status::UnprocessableEntity);
 
     // register with invalid nonce
-    let resp = client.post("/api/auth/register").json(&json!({
+    let (challenge, difficulty) = common::challenge(&client, "register");
+    let body = common::nonce(json!({
         "name": "Alice",
         "email": "alice@example.com",
         "password": "supersecret",
-        "nonce": 1337,
-    })).dispatch();
+        "challenge": challenge,
+    }), difficulty, false);
+    let resp = client.post("/api/auth/register").json(&body).dispatch();
     assert_eq!(resp.status(), Status::PaymentRequired);
 
     // register
-    let resp = client.post("/api/auth/register").json(&json!({
+    let body = common::solve(&client, "register", json!({
         "name": "Alice",
         "email": "alice@example.com",
         "password": "supersecret",
-        "nonce": 143970,
-    })).dispatch();
+    }));
+    let resp = client.post("/api/auth/register").json(&body).dispatch();
     assert_eq!(resp.status(), Status::NoContent);
 
     // receive email and extract token
@@ -76,10 +79,11 @@ fn test_auth() {
     assert_eq!(resp.status(), Status::Unauthorized);
 
     // change password
-    let resp = client.post("/api/auth/password/reset").json(&json!({
-        "email": "alice@example.com",
-        "nonce": 6542,
-    })).dispatch();
+    let resp = client.post("/api/auth/password/reset")
+        .json(&common::solve(&client, "password_reset", json!({
+            "email": "alice@example.com",
+        })))
+        .dispatch();
     assert_eq!(resp.status(), Status::NoContent);
 
     // receive email and extract token
@@ -118,6 +122,136 @@ fn test_auth() {
     assert_eq!(resp.status(), Status::Ok);
     let login: LoginResponse = resp.into_json().unwrap();
     assert_eq!(login.name, "Alice");
+
+    // check that only token hashes are stored
+    let tokens: Vec<String> = common::query(&client, "
+        SELECT VALUE token FROM login
+    ");
+    assert_eq!(tokens.len(), 1);
+    assert_ne!(tokens[0], login.token);
+    assert_eq!(tokens[0], format!("{:x}", Sha256::digest(&login.token)));
+}
+
+#[test]
+fn test_password_change() {
+    let client = common::client();
+
+    // register and create second session
+    let login: LoginResponse =
+        common::register(&client, "Alice", "alice@example.com");
+    let header = format!("apikey {}", login.token);
+    let other: LoginResponse = client.post("/api/auth/login").json(&json!({
+        "email": "alice@example.com", "password": "supersecret",
+    })).dispatch().into_json().unwrap();
+
+    // try changing password with wrong current password of any length
+    for current in ["wrongsecret", "short"] {
+        let resp = client.post("/api/auth/password/change")
+            .header(Header::new("Authorization", header.clone()))
+            .json(&json!({
+                "current_password": current, "password": "newsecret",
+            })).dispatch();
+        assert_eq!(resp.status(), Status::Forbidden);
+    }
+
+    // change password and logout other sessions
+    let resp = client.post("/api/auth/password/change")
+        .header(Header::new("Authorization", header.clone()))
+        .json(&json!({
+            "current_password": "supersecret", "password": "newsecret",
+            "logout_others": true,
+        })).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+
+    // receive notification email
+    let email = common::mailer(&client).receive_dummy().unwrap();
+    let content = String::from_utf8(email.formatted()).unwrap();
+    assert_eq!(&email.envelope().to()[0].to_string(), "alice@example.com");
+    assert!(content.contains("Hello Alice, the password of your account"));
+
+    // try using other session
+    let resp = client.post("/api/auth/logout")
+        .header(Header::new("Authorization", format!("apikey {}", other.token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    // login with new password
+    let resp = client.post("/api/auth/login").json(&json!({
+        "email": "alice@example.com", "password": "newsecret",
+    })).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // logout with current session
+    let resp = client.post("/api/auth/logout")
+        .header(Header::new("Authorization", header)).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+}
+
+#[test]
+fn test_email_change() {
+    let client = common::client();
+
+    // register two users
+    let alice: LoginResponse =
+        common::register(&client, "Alice", "alice@example.com");
+    let bob: LoginResponse =
+        common::register(&client, "Bob", "bob@example.com");
+
+    // request email changes to the same address for both users
+    let mut tokens = Vec::new();
+    for login in [&alice, &bob] {
+        let resp = client.post("/api/auth/email/change")
+            .header(Header::new("Authorization", format!("apikey {}", login.token)))
+            .json(&json!({ "email": "carol@example.com" })).dispatch();
+        assert_eq!(resp.status(), Status::NoContent);
+        tokens.push(receive_email_change(&client, &login.name));
+    }
+
+    // confirm first change with wrong token
+    let resp = client.post("/api/auth/email/confirm")
+        .json(&json!({ "token": "12345678911131517192123252729310" }))
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    // confirm first change
+    let resp = client.post("/api/auth/email/confirm")
+        .json(&json!({ "token": tokens[0] })).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+
+    // try confirming second change to now taken address
+    let resp = client.post("/api/auth/email/confirm")
+        .json(&json!({ "token": tokens[1] })).dispatch();
+    assert_eq!(resp.status(), Status::Conflict);
+
+    // login with new email address
+    let resp = client.post("/api/auth/login").json(&json!({
+        "email": "carol@example.com", "password": "supersecret",
+    })).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let login: LoginResponse = resp.into_json().unwrap();
+    assert_eq!(login.name, "Alice");
+}
+
+fn receive_email_change(client: &Client, name: &str) -> String {
+    // receive verification and notice emails in any order
+    let mut emails: Vec<(String, String)> = (0..2).map(|_| {
+        let email = common::mailer(client).receive_dummy().unwrap();
+        let to = email.envelope().to()[0].to_string();
+        (to, String::from_utf8(email.formatted()).unwrap())
+    }).collect();
+    emails.sort();
+
+    // check notice to old address
+    let (to, content) = &emails[0];
+    assert_eq!(to, &format!("{}@example.com", name.to_lowercase()));
+    assert!(content.contains("address to carol@example.com has been"));
+
+    // extract token from verification email
+    let (to, content) = &emails[1];
+    assert_eq!(to, "carol@example.com");
+    content.split("\r\n")
+        .find(|l| l.starts_with("Your email change token:")).unwrap()
+        .rsplit_once(" ").unwrap().1.to_string()
 }
 
 #[derive(Deserialize)]
@@ -1,8 +1,24 @@
-use backend_template::{mail::Mailer, rocket};
-use rocket::local::blocking::Client;
+use backend_template::{mail::Mailer, purge, rocket};
+use rocket::{
+    http::Status, local::blocking::Client, tokio::runtime, Build, Rocket,
+};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha512};
 use std::env;
+use surrealdb::{engine::any::Any, sql::Duration, Surreal};
 
+#[allow(dead_code)]
 pub fn client() -> Client {
+    client_with(&[])
+}
+
+pub fn client_with(vars: &[(&str, &str)]) -> Client {
+    // construct rocket instance and test client
+    Client::untracked(rocket_with(vars)).expect("error creating test client")
+}
+
+pub fn rocket_with(vars: &[(&str, &str)]) -> Rocket<Build> {
     // set config variables for reproducibility
     env::set_var("DATABASE_ADDRESS", "memory");
     env::set_var("DATABASE_NAMESPACE", "test");
@@ -12,12 +28,87 @@ pub fn client() -> Client {
     env::set_var("API_OWNER", "owner@example.com:$argon2id$v=19$m=19456,t=2,p=1$Cs/sCdezmQUdBcu2ZM76rQ$c6Hg3Z0XLtVakCfGr+xazw96dDH5dRXm68r/9Jea2ks");
     env::set_var("FILES_PATH", "static");
     env::set_var("OPENAPI_ENABLE", "false");
+    for (key, value) in vars {
+        env::set_var(key, value);
+    }
 
-    // construct rocket instance and test client
-    Client::untracked(rocket()).expect("error creating test client")
+    // construct rocket instance
+    rocket()
 }
 
 #[allow(dead_code)]
-pub fn mailer<'r>(client: &'r Client) -> &'r Mailer {
+pub fn mailer(client: &Client) -> &Mailer {
     client.rocket().state::<Mailer>().unwrap()
 }
+
+#[allow(dead_code)]
+pub fn query<T: DeserializeOwned>(client: &Client, query: &str) -> Vec<T> {
+    let db = client.rocket().state::<Surreal<Any>>().unwrap();
+    let runtime = runtime::Builder::new_current_thread()
+        .enable_all().build().unwrap();
+    runtime.block_on(async { db.query(query).await?.take(0) }).unwrap()
+}
+
+#[allow(dead_code)]
+pub fn purge_with(client: &Client, grace_days: u64, retention_days: u64) {
+    let db = client.rocket().state::<Surreal<Any>>().unwrap();
+    let runtime = runtime::Builder::new_current_thread()
+        .enable_all().build().unwrap();
+    runtime.block_on(purge(
+        db, Duration::from_days(grace_days),
+        Duration::from_days(retention_days),
+    )).unwrap()
+}
+
+#[allow(dead_code)]
+pub fn register<T: DeserializeOwned + Send + 'static>(
+    client: &Client, name: &str, email: &str,
+) -> T {
+    // register
+    let resp = client.post("/api/auth/register").json(&solve(client, "register", json!({
+        "name": name, "email": email, "password": "supersecret",
+    }))).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+
+    // receive email and extract token
+    let email = mailer(client).receive_dummy().unwrap();
+    let content = String::from_utf8(email.formatted()).unwrap();
+    let token = content.split("\r\n")
+        .find(|l| l.starts_with("Your registration token:")).unwrap()
+        .rsplit_once(" ").unwrap().1;
+
+    // confirm registration and consume confirmation email
+    let resp = client.post("/api/auth/confirm")
+        .json(&json!({ "token": token })).dispatch();
+    mailer(client).receive_dummy().unwrap();
+    resp.into_json().unwrap()
+}
+
+#[allow(dead_code)]
+pub fn challenge(client: &Client, scope: &str) -> (String, u32) {
+    let resp = client.get(format!("/api/pow/challenge?scope={scope}"))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let body: Value = resp.into_json().unwrap();
+    let difficulty = body["difficulty"].as_u64().unwrap() as u32;
+    (body["challenge"].as_str().unwrap().into(), difficulty)
+}
+
+#[allow(dead_code)]
+pub fn solve(client: &Client, scope: &str, mut body: Value) -> Value {
+    let (challenge, difficulty) = challenge(client, scope);
+    body["challenge"] = challenge.into();
+    nonce(body, difficulty, true)
+}
+
+#[allow(dead_code)]
+pub fn nonce(mut body: Value, difficulty: u32, valid: bool) -> Value {
+    for nonce in 0.. {
+        body["nonce"] = nonce.into();
+        let hash = Sha512::digest(serde_json::to_vec(&body).unwrap());
+        let zeros = hash.iter().position(|b| *b != 0)
+            .map_or(512, |i| i as u32 * 8 + hash[i].leading_zeros());
+        if (zeros >= difficulty) == valid { break; }
+    }
+    body
+}
@@ -0,0 +1,99 @@
+use rocket::{
+    http::{Cookie, Header, SameSite, Status},
+    local::blocking::{Client, LocalResponse},
+};
+use serde_json::json;
+
+mod common;
+
+#[test]
+fn test_cookies() {
+    let client = common::client_with(&[("API_COOKIE_SESSIONS", "true")]);
+
+    // login and receive session and CSRF cookies
+    let resp = client.post("/api/auth/login").json(&json!({
+        "email": "owner@example.com", "password": "supersecret",
+    })).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let (session, csrf) = cookies(&resp);
+    assert_eq!(session.http_only(), Some(true));
+    assert_ne!(csrf.http_only(), Some(true));
+    for cookie in [&session, &csrf] {
+        assert_eq!(cookie.secure(), Some(true));
+        assert_eq!(cookie.same_site(), Some(SameSite::Strict));
+        assert!(cookie.max_age().is_some());
+    }
+    let raw = resp.cookies().get("session").unwrap().value().to_string();
+    let token: String = resp.into_json::<serde_json::Value>().unwrap()
+        ["token"].as_str().unwrap().into();
+    assert_eq!(session.value(), token);
+    assert!(!raw.contains(&token));
+
+    // authenticate safe requests via cookie only
+    let resp = client.get("/api/auth/sessions").private_cookie(session.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // reject state-changing requests without matching CSRF header
+    let resp = logout_all(&client, &session, None);
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = logout_all(&client, &session, Some("wrong"));
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    // keep authorization header working without CSRF header
+    let resp = client.post("/api/auth/logout")
+        .header(Header::new("Authorization", format!("apikey {token}")))
+        .dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+    let resp = client.get("/api/auth/sessions").private_cookie(session.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    // register and log out via cookie with CSRF header
+    let (session, csrf) = confirm(&client);
+    let resp = client.post("/api/auth/logout").private_cookie(session.clone())
+        .header(Header::new("X-CSRF-Token", csrf.value().to_string()))
+        .dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+    let cleared = resp.cookies().get("session").unwrap();
+    assert_eq!(cleared.value(), "");
+    let resp = client.get("/api/auth/sessions").private_cookie(session)
+        .dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+}
+
+fn cookies(resp: &LocalResponse) -> (Cookie<'static>, Cookie<'static>) {
+    let session = resp.cookies().get_private("session").unwrap();
+    let csrf = resp.cookies().get("csrf").unwrap().clone();
+    (session, csrf)
+}
+
+fn logout_all<'c>(
+    client: &'c Client, session: &Cookie<'static>, csrf: Option<&str>,
+) -> LocalResponse<'c> {
+    let mut req = client.post("/api/auth/logout-all")
+        .private_cookie(session.clone());
+    if let Some(csrf) = csrf {
+        req = req.header(Header::new("X-CSRF-Token", csrf.to_string()));
+    }
+    req.dispatch()
+}
+
+fn confirm(client: &Client) -> (Cookie<'static>, Cookie<'static>) {
+    let body = common::solve(client, "register", json!({
+        "name": "Alice", "email": "alice@example.com",
+        "password": "supersecret",
+    }));
+    let resp = client.post("/api/auth/register").json(&body).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+    let email = common::mailer(client).receive_dummy().unwrap();
+    let content = String::from_utf8(email.formatted()).unwrap();
+    let token = content.split("\r\n")
+        .find(|l| l.starts_with("Your registration token:")).unwrap()
+        .rsplit_once(" ").unwrap().1.to_string();
+    let resp = client.post("/api/auth/confirm")
+        .json(&json!({ "token": token })).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    common::mailer(client).receive_dummy().unwrap();
+    cookies(&resp)
+}
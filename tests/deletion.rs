@@ -0,0 +1,145 @@
+use rocket::{http::{Header, Status}, local::blocking::Client};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+mod common;
+
+#[test]
+fn test_deletion() {
+    let client = client();
+    let login: LoginResponse =
+        common::register(&client, "Alice", "alice@example.com");
+
+    // delete self
+    let resp = client.delete(format!("/api/users/{}", login.id))
+        .header(header(&login)).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+
+    // receive email with restore token
+    let token = restore_token(&client);
+
+    // try using revoked session, logging in, and deleting again
+    let resp = client.get(format!("/api/users/{}", login.id))
+        .header(header(&login)).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let resp = login_as(&client, "alice@example.com");
+    assert_eq!(resp.0, Status::Forbidden);
+    assert_eq!(resp.1["code"], "account_deleted");
+
+    // check user still existing
+    let users: Vec<Value> = common::query(&client, &format!(
+        "SELECT * FROM user:⟨{}⟩", login.id,
+    ));
+    assert!(users[0]["deleted"].is_string());
+
+    // try restoring account with unknown token
+    let resp = client.post("/api/auth/restore")
+        .json(&json!({ "token": "12345678911131517192123252729310" }))
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    // restore account and login
+    let resp = client.post("/api/auth/restore")
+        .json(&json!({ "token": token })).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+    let resp = login_as(&client, "alice@example.com");
+    assert_eq!(resp.0, Status::Ok);
+    let login: LoginResponse = serde_json::from_value(resp.1).unwrap();
+
+    // try restoring account again
+    let resp = client.post("/api/auth/restore")
+        .json(&json!({ "token": token })).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    // delete self again and let grace period pass
+    let resp = client.delete(format!("/api/users/{}", login.id))
+        .header(header(&login)).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+    let token = restore_token(&client);
+    let _: Vec<Value> = common::query(&client, &format!(
+        "UPDATE user:⟨{}⟩ SET deleted = time::now() - 2d", login.id,
+    ));
+
+    // check user and restore token being purged
+    common::purge_with(&client, 1, 365);
+    let users: Vec<Value> = common::query(&client, &format!(
+        "SELECT * FROM user:⟨{}⟩", login.id,
+    ));
+    assert!(users.is_empty());
+    let resp = client.post("/api/auth/restore")
+        .json(&json!({ "token": token })).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    // register email address again
+    let _: LoginResponse =
+        common::register(&client, "Alice", "alice@example.com");
+}
+
+#[test]
+fn test_deletion_by_owner() {
+    let client = client();
+    let resp = login_as(&client, "owner@example.com");
+    let owner: LoginResponse = serde_json::from_value(resp.1).unwrap();
+    let alice: LoginResponse =
+        common::register(&client, "Alice", "alice@example.com");
+    let bob: LoginResponse = common::register(&client, "Bob", "bob@example.com");
+
+    // delete user as owner without issuing restore token
+    let resp = client.delete(format!("/api/users/{}", bob.id))
+        .header(header(&owner)).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+    let tokens: Vec<Value> =
+        common::query(&client, "SELECT * FROM account_restore");
+    assert!(tokens.is_empty());
+    let resp = login_as(&client, "bob@example.com");
+    assert_eq!(resp.0, Status::Forbidden);
+    assert_eq!(resp.1["code"], "account_deleted");
+
+    // try restoring as regular user and restoring user not pending deletion
+    let resp = client.post(format!("/api/users/{}/restore", bob.id))
+        .header(header(&alice)).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = client.post(format!("/api/users/{}/restore", alice.id))
+        .header(header(&owner)).dispatch();
+    assert_eq!(resp.status(), Status::Conflict);
+
+    // restore user as owner and login
+    let resp = client.post(format!("/api/users/{}/restore", bob.id))
+        .header(header(&owner)).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let user: Value = resp.into_json().unwrap();
+    assert!(user.get("deleted").is_none());
+    let resp = login_as(&client, "bob@example.com");
+    assert_eq!(resp.0, Status::Ok);
+}
+
+fn client() -> Client {
+    common::client_with(&[("API_DELETION_GRACE_DAYS", "1")])
+}
+
+fn restore_token(client: &Client) -> String {
+    let email = common::mailer(client).receive_dummy().unwrap();
+    assert_eq!(email.envelope().to()[0].to_string(), "alice@example.com");
+    let content = String::from_utf8(email.formatted()).unwrap();
+    assert!(content.contains("permanently in 1 days"));
+    content.split("\r\n")
+        .find(|l| l.starts_with("Your restore token:")).unwrap()
+        .rsplit_once(" ").unwrap().1.to_string()
+}
+
+fn login_as(client: &Client, email: &str) -> (Status, Value) {
+    let resp = client.post("/api/auth/login")
+        .json(&json!({ "email": email, "password": "supersecret" }))
+        .dispatch();
+    (resp.status(), resp.into_json().unwrap())
+}
+
+fn header(login: &LoginResponse) -> Header<'static> {
+    Header::new("Authorization", format!("apikey {}", login.token))
+}
+
+#[derive(Deserialize)]
+struct LoginResponse {
+    id: String,
+    token: String,
+}
@@ -0,0 +1,72 @@
+use rocket::{http::{Header, Status}, local::blocking::Client};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+mod common;
+
+#[test]
+fn test_session_expiry() {
+    let client = common::client_with(&[
+        ("API_SESSION_LIFETIME", "3600"),
+        ("API_SESSION_IDLE_TIMEOUT", "600"),
+    ]);
+
+    // login and check expiry
+    let login = login(&client);
+    assert!(login.expires.starts_with("20"));
+    let header = format!("apikey {}", login.token);
+
+    // keep session alive by using it
+    backdate(&client, "last_used = time::now() - 500s");
+    let resp = client.get(format!("/api/users/{}", login.id))
+        .header(Header::new("Authorization", header.clone())).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let used: Vec<Value> = common::query(&client, "
+        SELECT id FROM login WHERE last_used > time::now() - 60s
+    ");
+    assert_eq!(used.len(), 1);
+
+    // try using session after absolute lifetime
+    backdate(&client, "expires = time::now() - 1s");
+    let resp = client.get(format!("/api/users/{}", login.id))
+        .header(Header::new("Authorization", header)).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    // try using session after idle timeout
+    let login = self::login(&client);
+    backdate(&client, "last_used = time::now() - 700s");
+    let resp = client.get(format!("/api/users/{}", login.id))
+        .header(Header::new("Authorization", format!("apikey {}", login.token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    // check expired sessions and challenges being purged
+    let _: Vec<Value> = common::query(&client, &format!("
+        CREATE login_challenge SET
+            token = 'expired', user = user:⟨{}⟩, expires = time::now() - 1s
+    ", login.id));
+    backdate(&client, "expires = time::now() - 1s");
+    common::purge_with(&client, 30, 365);
+    let rows: Vec<Value> = common::query(&client, "
+        array::concat((SELECT id FROM login), (SELECT id FROM login_challenge))
+    ");
+    assert!(rows.is_empty());
+}
+
+fn login(client: &Client) -> LoginResponse {
+    client.post("/api/auth/login").json(&json!({
+        "email": "owner@example.com", "password": "supersecret",
+    })).dispatch().into_json().unwrap()
+}
+
+fn backdate(client: &Client, assignment: &str) {
+    let _: Vec<Value> =
+        common::query(client, &format!("UPDATE login SET {assignment}"));
+}
+
+#[derive(Deserialize)]
+struct LoginResponse {
+    id: String,
+    token: String,
+    expires: String,
+}
@@ -0,0 +1,108 @@
+use backend_template::export::{Registry, Section};
+use rocket::{http::{Header, Status}, local::blocking::Client};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+mod common;
+
+#[test]
+fn test_export() {
+    let rocket = common::rocket_with(&[
+        ("API_PUBLIC_URL", "https://backend.example.com/"),
+    ]).manage(Registry::default().with(Section {
+        name: "greeting", query: "'Hello ' + $user.name",
+    }));
+    let client = Client::untracked(rocket).unwrap();
+    let login: LoginResponse =
+        common::register(&client, "Alice", "alice@example.com");
+    let other: LoginResponse = common::register(&client, "Bob", "bob@example.com");
+    let owner: LoginResponse = client.post("/api/auth/login").json(&json!({
+        "email": "owner@example.com", "password": "supersecret",
+    })).dispatch().into_json().unwrap();
+
+    // create some personal data
+    let resp = client.post("/api/auth/email/change").header(header(&login))
+        .json(&json!({ "email": "alicia@example.com" })).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+    common::mailer(&client).receive_dummy().unwrap();
+    common::mailer(&client).receive_dummy().unwrap();
+
+    // try exporting other user and without login
+    let resp = client.get(format!("/api/users/{}/export", other.id))
+        .header(header(&login)).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = client.get(format!("/api/users/{}/export", login.id)).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    // export own data
+    let resp = client.get(format!("/api/users/{}/export", login.id))
+        .header(header(&login)).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let export: Value = resp.into_json().unwrap();
+    assert_eq!(export["user"]["name"], "Alice");
+    assert_eq!(export["user"]["email"], "alice@example.com");
+    assert!(export["user"].get("password").is_none());
+    assert_eq!(export["sessions"].as_array().unwrap().len(), 1);
+    assert_eq!(export["email_changes"][0]["email"], "alicia@example.com");
+    assert!(export["email_changes"][0].get("token").is_none());
+    assert_eq!(export["password_resets"], json!([]));
+    assert_eq!(export["greeting"], "Hello Alice");
+    let actions: Vec<_> = export["audit_events"].as_array().unwrap().iter()
+        .map(|event| event["action"].as_str().unwrap()).collect();
+    assert_eq!(actions, ["auth.register", "auth.email_change_request"]);
+
+    // export other user with permission only if outranking them
+    let resp = client.post("/api/roles").header(header(&owner))
+        .json(&json!({ "name": "exporter", "permissions": ["users:export"] }))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Created);
+    let resp = client.patch(format!("/api/users/{}", other.id))
+        .header(header(&owner)).json(&json!({ "role": "exporter" }))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.get(format!("/api/users/{}/export", login.id))
+        .header(header(&other)).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.get(format!("/api/users/{}/export", owner.id))
+        .header(header(&other)).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    // request export by email
+    let resp = client.post(format!("/api/users/{}/export", login.id))
+        .header(header(&login)).dispatch();
+    assert_eq!(resp.status(), Status::Accepted);
+
+    // receive email and extract download link
+    let email = common::mailer(&client).receive_dummy().unwrap();
+    assert_eq!(email.envelope().to()[0].to_string(), "alice@example.com");
+    let content = String::from_utf8(email.formatted()).unwrap()
+        .replace("=\r\n", "");
+    let prefix = "https://backend.example.com/api/users/";
+    let url = content.split("\r\n").find(|l| l.starts_with(prefix)).unwrap();
+    let path = url.strip_prefix("https://backend.example.com").unwrap();
+
+    // download export
+    let resp = client.get(path).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let download: Value = resp.into_json().unwrap();
+    assert_eq!(download["user"], export["user"]);
+
+    // try downloading with wrong user or token
+    let (_, token) = path.rsplit_once('/').unwrap();
+    let resp = client.get(format!("/api/users/{}/export/{token}", other.id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+    let resp = client.get(format!("/api/users/{}/export/unknown", login.id))
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+fn header(login: &LoginResponse) -> Header<'static> {
+    Header::new("Authorization", format!("apikey {}", login.token))
+}
+
+#[derive(Deserialize)]
+struct LoginResponse {
+    id: String,
+    token: String,
+}
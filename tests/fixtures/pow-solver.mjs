@@ -0,0 +1,27 @@
+// Generate pow-solver.json by running the shipped solver module and worker.
+//
+// Usage: node tests/fixtures/pow-solver.mjs > tests/fixtures/pow-solver.json
+
+import { createHash } from "node:crypto";
+
+const assets = new URL("../../static/http/assets/pow/v1/", import.meta.url);
+const { prefix } = await import(new URL("solver.js", assets));
+
+const input = {
+  name: "Zoë ✓", email: "zoe@example.com", password: "supersecret",
+};
+const challenge = "fixture.challenge";
+const difficulty = 12;
+
+// run worker with a stand-in for the worker global scope
+const body = await new Promise(resolve => {
+  globalThis.self = { postMessage: resolve };
+  import(new URL("worker.js", assets)).then(() =>
+    self.onmessage({ data: { prefix: prefix(input, challenge), difficulty } })
+  );
+});
+
+const hash = createHash("sha512").update(body, "utf8").digest("hex");
+console.log(JSON.stringify(
+  { input, challenge, difficulty, body, hash }, null, 2,
+));
@@ -0,0 +1,110 @@
+use rocket::{
+    http::{Header, Status}, local::blocking::{Client, LocalResponse},
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+mod common;
+
+#[test]
+fn test_impersonation() {
+    let client = common::client();
+    let login: LoginResponse = client.post("/api/auth/login").json(&json!({
+        "email": "owner@example.com", "password": "supersecret",
+    })).dispatch().into_json().unwrap();
+    let owner = header(&login.token);
+    let alice: LoginResponse =
+        common::register(&client, "Alice", "alice@example.com");
+    let alice_h = header(&alice.token);
+
+    // try impersonating without permission, unknown user, and self
+    let resp = impersonate(&client, &alice_h, &login.id);
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = impersonate(&client, &owner, "unknown");
+    assert_eq!(resp.status(), Status::NotFound);
+    let resp = impersonate(&client, &owner, &login.id);
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    // impersonate user with short-lived session
+    let resp = impersonate(&client, &owner, &alice.id);
+    assert_eq!(resp.status(), Status::Ok);
+    let session: LoginResponse = resp.into_json().unwrap();
+    assert_eq!(session.id, alice.id);
+    let impersonation = header(&session.token);
+    let short: Vec<bool> = common::query(&client, "
+        SELECT VALUE expires < time::now() + 16m FROM login WHERE impersonator
+    ");
+    assert_eq!(short, [true]);
+
+    // act as user, but try sensitive actions
+    let resp = client.get(format!("/api/users/{}", alice.id))
+        .header(impersonation.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.post("/api/auth/password/change")
+        .header(impersonation.clone()).json(&json!({
+            "current_password": "supersecret", "password": "newpassword",
+        })).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = client.post("/api/auth/2fa/setup")
+        .header(impersonation.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = client.delete(format!("/api/users/{}", alice.id))
+        .header(impersonation.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    // list and revoke impersonation session as impersonated user
+    let resp = client.get("/api/auth/sessions").header(alice_h.clone())
+        .dispatch();
+    let sessions: Vec<Value> = resp.into_json().unwrap();
+    let other = sessions.iter()
+        .find(|session| session["impersonator"] == json!(login.id)).unwrap();
+    let resp = client.delete(format!("/api/auth/sessions/{}", other["id"]
+        .as_str().unwrap())).header(alice_h.clone()).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+    let resp = client.get(format!("/api/users/{}", alice.id))
+        .header(impersonation).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    // impersonate again and stop by logging out
+    let resp = impersonate(&client, &owner, &alice.id);
+    let session: LoginResponse = resp.into_json().unwrap();
+    let resp = client.post("/api/auth/logout").header(header(&session.token))
+        .dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+
+    // check start and stop being audited
+    let resp = client.get(format!("/api/audit?target={}", alice.id))
+        .header(owner.clone()).dispatch();
+    let page: Value = resp.into_json().unwrap();
+    let events: Vec<_> = page["items"].as_array().unwrap().iter()
+        .filter(|event| event["actor"] == json!(login.id))
+        .map(|event| event["action"].as_str().unwrap()).collect();
+    assert_eq!(events, [
+        "user.impersonate_stop", "user.impersonate_start",
+        "user.impersonate_start",
+    ]);
+
+    // try impersonating suspended user
+    let resp = client.post(format!("/api/users/{}/suspend", alice.id))
+        .header(owner.clone()).json(&json!({ "reason": "Test" })).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = impersonate(&client, &owner, &alice.id);
+    assert_eq!(resp.status(), Status::Conflict);
+}
+
+fn impersonate<'c>(
+    client: &'c Client, header: &Header<'static>, id: &str,
+) -> LocalResponse<'c> {
+    client.post(format!("/api/users/{id}/impersonate")).header(header.clone())
+        .dispatch()
+}
+
+fn header(token: &str) -> Header<'static> {
+    Header::new("Authorization", format!("apikey {token}"))
+}
+
+#[derive(Deserialize)]
+struct LoginResponse {
+    id: String,
+    token: String,
+}
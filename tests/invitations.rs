@@ -0,0 +1,163 @@
+use rocket::{http::{Header, Status}, local::blocking::Client};
+use serde::Deserialize;
+use serde_json::json;
+
+mod common;
+
+#[test]
+fn test_create_user() {
+    let client = common::client();
+    let owner = owner_header(&client);
+
+    // create user with password
+    let resp = client.post("/api/users").header(owner.clone()).json(&json!({
+        "name": "Alice", "email": "Alice@example.com",
+        "password": "supersecret", "role": "admin",
+    })).dispatch();
+    assert_eq!(resp.status(), Status::Created);
+    let user: UserResponse = resp.into_json().unwrap();
+    assert_eq!((user.name.as_str(), user.role.as_str()), ("Alice", "admin"));
+
+    // login created user
+    let resp = client.post("/api/auth/login").json(&json!({
+        "email": "alice@example.com", "password": "supersecret",
+    })).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let login: LoginResponse = resp.into_json().unwrap();
+    let admin = Header::new("Authorization", format!("apikey {}", login.token));
+
+    // try creating user with taken email address
+    let resp = client.post("/api/users").header(admin.clone()).json(&json!({
+        "name": "Alicia", "email": "alice@example.com",
+        "password": "supersecret",
+    })).dispatch();
+    assert_eq!(resp.status(), Status::Conflict);
+
+    // try creating admin as admin
+    let resp = client.post("/api/users").header(admin.clone()).json(&json!({
+        "name": "Bob", "email": "bob@example.com",
+        "password": "supersecret", "role": "admin",
+    })).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    // try creating owner and user with unknown role
+    let resp = client.post("/api/users").header(owner.clone()).json(&json!({
+        "name": "Bob", "email": "bob@example.com",
+        "password": "supersecret", "role": "owner",
+    })).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = client.post("/api/users").header(owner).json(&json!({
+        "name": "Bob", "email": "bob@example.com",
+        "password": "supersecret", "role": "superuser",
+    })).dispatch();
+    assert_eq!(resp.status(), Status::UnprocessableEntity);
+
+    // create user as admin with default role
+    let resp = client.post("/api/users").header(admin).json(&json!({
+        "name": "Bob", "email": "bob@example.com", "password": "supersecret",
+    })).dispatch();
+    assert_eq!(resp.status(), Status::Created);
+    let user: UserResponse = resp.into_json().unwrap();
+    assert_eq!(user.role, "user");
+
+    // try creating user without login
+    let resp = client.post("/api/users").json(&json!({
+        "name": "Carol", "email": "carol@example.com",
+    })).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+}
+
+#[test]
+fn test_invitation() {
+    let client = common::client();
+    let owner = owner_header(&client);
+
+    // invite user
+    let resp = client.post("/api/users").header(owner.clone()).json(&json!({
+        "name": "Carol", "email": "carol@example.com", "role": "admin",
+    })).dispatch();
+    assert_eq!(resp.status(), Status::Accepted);
+    let invitation: InvitationResponse = resp.into_json().unwrap();
+    assert_eq!(invitation.email, "carol@example.com");
+    assert_eq!(invitation.role, "admin");
+    assert!(!invitation.expires.is_empty());
+
+    // receive invitation email and extract token
+    let email = common::mailer(&client).receive_dummy().unwrap();
+    assert_eq!(email.envelope().to()[0].to_string(), "carol@example.com");
+    let content = String::from_utf8(email.formatted()).unwrap();
+    let token = content.split("\r\n")
+        .find(|l| l.starts_with("Your invitation token:")).unwrap()
+        .rsplit_once(" ").unwrap().1.to_string();
+
+    // try inviting the same email address again
+    let resp = client.post("/api/users").header(owner).json(&json!({
+        "name": "Carol", "email": "carol@example.com",
+    })).dispatch();
+    assert_eq!(resp.status(), Status::Conflict);
+
+    // try registering invited email address
+    let body = common::solve(&client, "register", json!({
+        "name": "Carol", "email": "carol@example.com",
+        "password": "supersecret",
+    }));
+    let resp = client.post("/api/auth/register").json(&body).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+
+    // try accepting invitation with short password
+    let resp = client.post("/api/auth/accept")
+        .json(&json!({ "token": token, "password": "short" })).dispatch();
+    assert_eq!(resp.status(), Status::UnprocessableEntity);
+
+    // accept invitation
+    let resp = client.post("/api/auth/accept")
+        .json(&json!({ "token": token, "password": "supersecret" }))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let login: LoginResponse = resp.into_json().unwrap();
+    assert_eq!((login.name.as_str(), login.role.as_str()), ("Carol", "admin"));
+
+    // receive welcome email without verification email for registration
+    let email = common::mailer(&client).receive_dummy().unwrap();
+    let content = String::from_utf8(email.formatted()).unwrap();
+    assert!(content.contains("Welcome aboard"));
+
+    // try accepting invitation again
+    let resp = client.post("/api/auth/accept")
+        .json(&json!({ "token": token, "password": "supersecret" }))
+        .dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    // login with chosen password
+    let resp = client.post("/api/auth/login").json(&json!({
+        "email": "carol@example.com", "password": "supersecret",
+    })).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+}
+
+fn owner_header(client: &Client) -> Header<'static> {
+    let login: LoginResponse = client.post("/api/auth/login").json(&json!({
+        "email": "owner@example.com", "password": "supersecret",
+    })).dispatch().into_json().unwrap();
+    Header::new("Authorization", format!("apikey {}", login.token))
+}
+
+#[derive(Deserialize)]
+struct LoginResponse {
+    name: String,
+    role: String,
+    token: String,
+}
+
+#[derive(Deserialize)]
+struct UserResponse {
+    name: String,
+    role: String,
+}
+
+#[derive(Deserialize)]
+struct InvitationResponse {
+    email: String,
+    role: String,
+    expires: String,
+}
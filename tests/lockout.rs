@@ -0,0 +1,105 @@
+use rocket::{
+    http::{Header, Status}, local::blocking::{Client, LocalResponse},
+};
+use serde::Deserialize;
+use std::{thread::sleep, time::Duration};
+
+mod common;
+
+#[test]
+fn test_email_lockout() {
+    let client = client();
+    let _: LoginResponse =
+        common::register(&client, "Alice", "alice@example.com");
+
+    // fail until lockout
+    for _ in 0..3 {
+        let resp = login(&client, "alice@example.com", "wrongpassword", "10.0.0.1");
+        assert_eq!(resp.status(), Status::Unauthorized);
+    }
+
+    // receive lockout notification
+    let email = common::mailer(&client).receive_dummy().unwrap();
+    assert_eq!(email.envelope().to()[0].to_string(), "alice@example.com");
+    let content = String::from_utf8(email.formatted()).unwrap();
+    assert!(content.contains("temporarily locked"));
+
+    // try login with correct password from another IP
+    let resp = login(&client, "alice@example.com", "supersecret", "10.0.0.2");
+    assert_eq!(resp.status(), Status::TooManyRequests);
+    assert!(matches!(resp.headers().get_one("Retry-After"), Some("1" | "2")));
+
+    // check lockout counting from attempt, as password hashing takes a while
+    assert_eq!(lockout(&client), [2]);
+
+    // fail again after lockout with doubled lockout
+    sleep(Duration::from_secs(2));
+    let resp = login(&client, "alice@example.com", "wrongpassword", "10.0.0.1");
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let resp = login(&client, "alice@example.com", "supersecret", "10.0.0.2");
+    assert_eq!(resp.status(), Status::TooManyRequests);
+    assert_eq!(lockout(&client), [4]);
+
+    // login after lockout and check failures being reset
+    sleep(Duration::from_secs(4));
+    let resp = login(&client, "alice@example.com", "supersecret", "10.0.0.2");
+    assert_eq!(resp.status(), Status::Ok);
+    let failures: Vec<u64> = common::query(&client, "
+        SELECT VALUE failures FROM login_attempt:⟨email:alice@example.com⟩
+    ");
+    assert!(failures.is_empty());
+}
+
+#[test]
+fn test_ip_lockout() {
+    let client = client();
+
+    // fail with different email addresses until lockout
+    for n in 0..5 {
+        let email = format!("user{n}@example.com");
+        let resp = login(&client, &email, "wrongpassword", "10.0.0.3");
+        assert_eq!(resp.status(), Status::Unauthorized);
+    }
+
+    // try login from locked IP
+    let resp = login(&client, "owner@example.com", "supersecret", "10.0.0.3");
+    assert_eq!(resp.status(), Status::TooManyRequests);
+    let problem: serde_json::Value = resp.into_json().unwrap();
+    assert_eq!(problem["code"], "too_many_requests");
+
+    // login from another IP
+    let resp = login(&client, "owner@example.com", "supersecret", "10.0.0.4");
+    assert_eq!(resp.status(), Status::Ok);
+}
+
+fn client() -> Client {
+    common::client_with(&[
+        ("API_LOGIN_MAX_FAILURES", "3"),
+        ("API_LOGIN_MAX_IP_FAILURES", "5"),
+        ("API_LOGIN_LOCKOUT", "2"),
+        ("API_LOGIN_LOCKOUT_MAX", "4"),
+        ("API_IP_HEADER", "X-Real-IP"),
+    ])
+}
+
+fn lockout(client: &Client) -> Vec<u64> {
+    common::query(client, "
+        SELECT VALUE duration::secs(locked_until - last_failure)
+        FROM login_attempt:⟨email:alice@example.com⟩
+    ")
+}
+
+fn login<'c>(
+    client: &'c Client, email: &str, password: &str, ip: &str,
+) -> LocalResponse<'c> {
+    client.post("/api/auth/login")
+        .header(Header::new("X-Real-IP", ip.to_string()))
+        .json(&serde_json::json!({ "email": email, "password": password }))
+        .dispatch()
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct LoginResponse {
+    token: String,
+}
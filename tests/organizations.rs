@@ -0,0 +1,180 @@
+use rocket::{
+    http::{Header, Status}, local::blocking::{Client, LocalResponse},
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+mod common;
+
+#[test]
+fn test_organizations() {
+    let client = common::client();
+    let alice: LoginResponse =
+        common::register(&client, "Alice", "alice@example.com");
+    let bob: LoginResponse = common::register(&client, "Bob", "bob@example.com");
+    let carol: LoginResponse =
+        common::register(&client, "Carol", "carol@example.com");
+    let (alice_h, bob_h) = (header(&alice), header(&bob));
+    let carol_h = header(&carol);
+
+    // try creating organization with invalid name and create it
+    let resp = client.post("/api/organizations").header(alice_h.clone())
+        .json(&json!({ "name": "A" })).dispatch();
+    assert_eq!(resp.status(), Status::UnprocessableEntity);
+    let resp = client.post("/api/organizations").header(alice_h.clone())
+        .json(&json!({ "name": "Acme" })).dispatch();
+    assert_eq!(resp.status(), Status::Created);
+    let org: OrganizationResponse = resp.into_json().unwrap();
+    assert_eq!((org.name.as_str(), org.role.as_str()), ("Acme", "org_owner"));
+    let base = format!("/api/organizations/{}", org.id);
+
+    // list organizations of member and non-member
+    let resp = client.get("/api/organizations").header(alice_h.clone())
+        .dispatch();
+    let orgs: Vec<OrganizationResponse> = resp.into_json().unwrap();
+    assert_eq!(orgs.len(), 1);
+    let resp = client.get("/api/organizations").header(bob_h.clone())
+        .dispatch();
+    let orgs: Vec<OrganizationResponse> = resp.into_json().unwrap();
+    assert!(orgs.is_empty());
+
+    // try accessing organization without membership and login
+    let resp = client.get(&base).header(bob_h.clone()).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+    let resp = client.get(&base).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    // try inviting existing member and with unknown role
+    let resp = invite(&client, &alice_h, &base, json!({
+        "email": "ALICE@example.com",
+    }));
+    assert_eq!(resp.status(), Status::Conflict);
+    let resp = invite(&client, &alice_h, &base, json!({
+        "email": "bob@example.com", "role": "superuser",
+    }));
+    assert_eq!(resp.status(), Status::UnprocessableEntity);
+
+    // invite member and receive invitation email
+    let resp = invite(&client, &alice_h, &base, json!({
+        "email": "bob@example.com",
+    }));
+    assert_eq!(resp.status(), Status::Accepted);
+    let invitation: Value = resp.into_json().unwrap();
+    assert_eq!(invitation["role"], "user");
+    let email = common::mailer(&client).receive_dummy().unwrap();
+    assert_eq!(email.envelope().to()[0].to_string(), "bob@example.com");
+    let content = String::from_utf8(email.formatted()).unwrap()
+        .replace("=\r\n", "");
+    assert!(content.contains("Alice has invited you to join the organization \
+        Acme"));
+    let token = content.split("\r\n")
+        .find(|l| l.starts_with("Your invitation token:")).unwrap()
+        .rsplit_once(" ").unwrap().1.to_string();
+
+    // try joining with other email address and join
+    let resp = join(&client, &carol_h, &token);
+    assert_eq!(resp.status(), Status::NotFound);
+    let resp = join(&client, &bob_h, &token);
+    assert_eq!(resp.status(), Status::Ok);
+    let joined: OrganizationResponse = resp.into_json().unwrap();
+    assert_eq!((joined.id, joined.role.as_str()), (org.id.clone(), "user"));
+    let resp = join(&client, &bob_h, &token);
+    assert_eq!(resp.status(), Status::NotFound);
+
+    // try listing members as user and list them as owner
+    let members = format!("{base}/members");
+    let resp = client.get(&members).header(bob_h.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = client.get(&members).header(alice_h.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let page: Value = resp.into_json().unwrap();
+    assert_eq!(page["total"], 2);
+    assert_eq!(page["items"][1]["email"], "bob@example.com");
+
+    // try assigning global role and promote member to organization admin,
+    // who can then list members
+    let resp = client.patch(format!("{members}/{}", bob.id))
+        .header(alice_h.clone()).json(&json!({ "role": "admin" })).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = client.patch(format!("{members}/{}", bob.id))
+        .header(alice_h.clone()).json(&json!({ "role": "org_admin" }))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.get(&members).header(bob_h.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // try renaming organization, promoting self, and removing owner as admin
+    let resp = client.patch(&base).header(bob_h.clone())
+        .json(&json!({ "name": "Bobcorp" })).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = client.patch(format!("{members}/{}", bob.id))
+        .header(bob_h.clone()).json(&json!({ "role": "org_owner" }))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = client.delete(format!("{members}/{}", alice.id))
+        .header(bob_h.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    // rename organization as owner
+    let resp = client.patch(&base).header(alice_h.clone())
+        .json(&json!({ "name": "Acme Inc." })).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let renamed: OrganizationResponse = resp.into_json().unwrap();
+    assert_eq!(renamed.name, "Acme Inc.");
+
+    // try leaving as last owner, add co-owner, and leave as co-owner
+    let resp = client.delete(format!("{members}/{}", alice.id))
+        .header(alice_h.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Conflict);
+    let resp = client.patch(format!("{members}/{}", bob.id))
+        .header(alice_h.clone()).json(&json!({ "role": "org_owner" }))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.delete(format!("{members}/{}", bob.id))
+        .header(bob_h.clone()).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+    let resp = client.get(&base).header(bob_h.clone()).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    // delete organization
+    let resp = client.delete(&base).header(alice_h.clone()).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+    let resp = client.get("/api/organizations").header(alice_h).dispatch();
+    let orgs: Vec<OrganizationResponse> = resp.into_json().unwrap();
+    assert!(orgs.is_empty());
+    let memberships: Vec<Value> = common::query(&client, "
+        SELECT * FROM membership
+    ");
+    assert!(memberships.is_empty());
+}
+
+fn invite<'c>(
+    client: &'c Client, header: &Header<'static>, base: &str, body: Value,
+) -> LocalResponse<'c> {
+    client.post(format!("{base}/members")).header(header.clone()).json(&body)
+        .dispatch()
+}
+
+fn join<'c>(
+    client: &'c Client, header: &Header<'static>, token: &str,
+) -> LocalResponse<'c> {
+    client.post("/api/organizations/join").header(header.clone())
+        .json(&json!({ "token": token })).dispatch()
+}
+
+fn header(login: &LoginResponse) -> Header<'static> {
+    Header::new("Authorization", format!("apikey {}", login.token))
+}
+
+#[derive(Deserialize)]
+struct LoginResponse {
+    id: String,
+    token: String,
+}
+
+#[derive(Deserialize)]
+struct OrganizationResponse {
+    id: String,
+    name: String,
+    role: String,
+}
@@ -0,0 +1,151 @@
+use rocket::{
+    http::{Header, Status}, local::blocking::{Client, LocalResponse},
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+mod common;
+
+#[test]
+fn test_ownership() {
+    let client = common::client_with(&[("API_OWNER_BOOTSTRAP", "true")]);
+    let login: LoginResponse = client.post("/api/auth/login").json(&json!({
+        "email": "owner@example.com", "password": "supersecret",
+    })).dispatch().into_json().unwrap();
+    let owner = header(&login);
+    let alice: LoginResponse =
+        common::register(&client, "Alice", "alice@example.com");
+    let bob: LoginResponse = common::register(&client, "Bob", "bob@example.com");
+    let (alice_h, bob_h) = (header(&alice), header(&bob));
+
+    // try offering ownership without permission, to unknown user, and self
+    let resp = offer(&client, &alice_h, json!({ "email": "bob@example.com" }));
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = offer(&client, &owner, json!({ "email": "eve@example.com" }));
+    assert_eq!(resp.status(), Status::NotFound);
+    let resp = offer(&client, &owner, json!({ "email": "owner@example.com" }));
+    assert_eq!(resp.status(), Status::Conflict);
+
+    // offer ownership and receive email
+    let resp = offer(&client, &owner, json!({ "email": "ALICE@example.com" }));
+    assert_eq!(resp.status(), Status::Accepted);
+    let transfer: Value = resp.into_json().unwrap();
+    assert_eq!(transfer["email"], "alice@example.com");
+    assert_eq!(transfer["keep"], false);
+    let token = receive_token(&client, "alice@example.com", "the owner");
+
+    // try accepting as other user and accept as recipient
+    let resp = accept(&client, &bob_h, &token);
+    assert_eq!(resp.status(), Status::NotFound);
+    let resp = accept(&client, &alice_h, &token);
+    assert_eq!(resp.status(), Status::NoContent);
+    let resp = accept(&client, &alice_h, &token);
+    assert_eq!(resp.status(), Status::NotFound);
+
+    // check roles after handing over ownership
+    assert_eq!(role(&client, &alice_h, &alice.id), "owner");
+    assert_eq!(role(&client, &alice_h, &login.id), "admin");
+    let resp = offer(&client, &owner, json!({ "email": "bob@example.com" }));
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    // add co-owner
+    let resp = offer(&client, &alice_h, json!({
+        "email": "bob@example.com", "keep": true,
+    }));
+    assert_eq!(resp.status(), Status::Accepted);
+    let token = receive_token(&client, "bob@example.com", "a co-owner");
+    let resp = accept(&client, &bob_h, &token);
+    assert_eq!(resp.status(), Status::NoContent);
+    assert_eq!(role(&client, &alice_h, &alice.id), "owner");
+    assert_eq!(role(&client, &alice_h, &bob.id), "owner");
+
+    // hand over ownership from custom role with permission until revoked
+    let carol: LoginResponse =
+        common::register(&client, "Carol", "carol@example.com");
+    let dave: LoginResponse =
+        common::register(&client, "Dave", "dave@example.com");
+    let resp = client.post("/api/roles").header(alice_h.clone())
+        .json(&json!({ "name": "steward", "permissions": [] })).dispatch();
+    assert_eq!(resp.status(), Status::Created);
+    let resp = client.patch(format!("/api/users/{}", carol.id))
+        .header(alice_h.clone()).json(&json!({ "role": "steward" }))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let carol_h = header(&carol);
+    let steward = |permissions: Value| {
+        let resp = client.patch("/api/roles/steward").header(alice_h.clone())
+            .json(&json!({ "permissions": permissions })).dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+    };
+    for revoke in [true, false] {
+        steward(json!(["ownership:transfer"]));
+        let resp = offer(&client, &carol_h, json!({
+            "email": "dave@example.com",
+        }));
+        assert_eq!(resp.status(), Status::Accepted);
+        let token = receive_token(&client, "dave@example.com", "the owner");
+        if revoke {
+            steward(json!([]));
+        }
+        let resp = accept(&client, &header(&dave), &token);
+        let status = match revoke {
+            true => Status::NotFound,
+            false => Status::NoContent,
+        };
+        assert_eq!(resp.status(), status);
+    }
+    assert_eq!(role(&client, &alice_h, &dave.id), "owner");
+    assert_eq!(role(&client, &alice_h, &carol.id), "steward");
+
+    // check transfers being audited
+    let resp = client.get("/api/audit?action=ownership.accept")
+        .header(alice_h).dispatch();
+    let page: Value = resp.into_json().unwrap();
+    assert_eq!(page["total"], 3);
+    assert_eq!(page["items"][0]["actor"], carol.id);
+    assert_eq!(page["items"][0]["diff"]["after"]["sender_role"], "steward");
+    assert_eq!(page["items"][2]["actor"], login.id);
+    assert_eq!(page["items"][2]["diff"]["after"]["sender_role"], "admin");
+}
+
+fn offer<'c>(
+    client: &'c Client, header: &Header<'static>, body: Value,
+) -> LocalResponse<'c> {
+    client.post("/api/ownership").header(header.clone()).json(&body)
+        .dispatch()
+}
+
+fn accept<'c>(
+    client: &'c Client, header: &Header<'static>, token: &str,
+) -> LocalResponse<'c> {
+    client.post("/api/ownership/accept").header(header.clone())
+        .json(&json!({ "token": token })).dispatch()
+}
+
+fn receive_token(client: &Client, address: &str, offer: &str) -> String {
+    let email = common::mailer(client).receive_dummy().unwrap();
+    assert_eq!(email.envelope().to()[0].to_string(), address);
+    let content = String::from_utf8(email.formatted()).unwrap()
+        .replace("=\r\n", "");
+    assert!(content.contains(&format!("has offered to make you {offer}")));
+    content.split("\r\n")
+        .find(|l| l.starts_with("Your ownership token:")).unwrap()
+        .rsplit_once(" ").unwrap().1.to_string()
+}
+
+fn role(client: &Client, header: &Header<'static>, id: &str) -> String {
+    let resp = client.get(format!("/api/users/{id}")).header(header.clone())
+        .dispatch();
+    let user: Value = resp.into_json().unwrap();
+    user["role"].as_str().unwrap().into()
+}
+
+fn header(login: &LoginResponse) -> Header<'static> {
+    Header::new("Authorization", format!("apikey {}", login.token))
+}
+
+#[derive(Deserialize)]
+struct LoginResponse {
+    id: String,
+    token: String,
+}
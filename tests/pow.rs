@@ -0,0 +1,216 @@
+use hmac::{Hmac, Mac};
+use rocket::{http::{ContentType, Status}, local::blocking::Client};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256, Sha512};
+
+mod common;
+
+#[test]
+fn test_pow_challenge() {
+    let client = client();
+
+    // try getting challenge for unknown scope
+    let resp = client.get("/api/pow/challenge?scope=unknown").dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    // get challenge
+    let resp = client.get("/api/pow/challenge?scope=password_reset")
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let challenge: ChallengeResponse = resp.into_json().unwrap();
+    assert_eq!(challenge.challenge.split('.').count(), 4);
+    assert_eq!(challenge.difficulty, 4);
+    assert!(!challenge.expires.is_empty());
+
+    // check challenges being issued without storing them
+    let stored: Vec<Value> =
+        common::query(&client, "SELECT * FROM pow_challenge");
+    assert!(stored.is_empty());
+
+    // try proof of work without challenge
+    let body = common::nonce(json!({ "email": "alice@example.com" }), 8, true);
+    let resp = client.post("/api/auth/password/reset").json(&body).dispatch();
+    assert_eq!(resp.status(), Status::PaymentRequired);
+
+    // try proof of work with unknown and tampered challenges
+    let (_, rest) = challenge.challenge.split_once('.').unwrap();
+    for forged in [
+        "12345678911131517192123252729310".to_string(),
+        format!("{}.{rest}", "x".repeat(32)),
+        challenge.challenge.replacen(".4.", ".0.", 1),
+    ] {
+        let body = common::nonce(json!({
+            "email": "alice@example.com", "challenge": forged,
+        }), 8, true);
+        let resp = client.post("/api/auth/password/reset").json(&body)
+            .dispatch();
+        assert_eq!(resp.status(), Status::PaymentRequired);
+    }
+
+    // send proof of work with issued challenge
+    let body = common::nonce(json!({
+        "email": "alice@example.com",
+        "challenge": challenge.challenge,
+    }), challenge.difficulty, true);
+    let resp = client.post("/api/auth/password/reset").json(&body).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+
+    // try replaying request body
+    let resp = client.post("/api/auth/password/reset").json(&body).dispatch();
+    assert_eq!(resp.status(), Status::PaymentRequired);
+
+    // try using challenge of another scope
+    let (challenge, _) = common::challenge(&client, "register");
+    let body = common::nonce(json!({
+        "email": "alice@example.com", "challenge": challenge,
+    }), 10, true);
+    let resp = client.post("/api/auth/password/reset").json(&body).dispatch();
+    assert_eq!(resp.status(), Status::PaymentRequired);
+
+    // check expired challenges being rejected despite valid signature
+    let key: Vec<String> =
+        common::query(&client, "SELECT VALUE value FROM secret:pow");
+    let expires = rocket::time::OffsetDateTime::now_utc().unix_timestamp() - 1;
+    let payload = format!("{}.{expires}.4", "x".repeat(32));
+    let mut mac = Hmac::<Sha256>::new_from_slice(key[0].as_bytes()).unwrap();
+    mac.update(format!("password_reset.{payload}").as_bytes());
+    let challenge = format!("{payload}.{:x}", mac.finalize().into_bytes());
+    let body = common::nonce(json!({
+        "email": "alice@example.com", "challenge": challenge,
+    }), 4, true);
+    let resp = client.post("/api/auth/password/reset").json(&body).dispatch();
+    assert_eq!(resp.status(), Status::PaymentRequired);
+
+    // check spent challenges being stored until they expire
+    let stored: Vec<Value> =
+        common::query(&client, "SELECT * FROM pow_challenge");
+    assert_eq!(stored.len(), 1);
+}
+
+#[test]
+fn test_pow_body_limit() {
+    let client = client();
+
+    // send request body longer than 512 bytes
+    let body = common::solve(&client, "password_reset", json!({
+        "email": "alice@example.com", "padding": "x".repeat(600),
+    }));
+    let resp = client.post("/api/auth/password/reset").json(&body).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+
+    // try sending request body exceeding the limit
+    let body = common::solve(&client, "password_reset", json!({
+        "email": "alice@example.com", "padding": "x".repeat(1100),
+    }));
+    let resp = client.post("/api/auth/password/reset").json(&body).dispatch();
+    assert_eq!(resp.status(), Status::PayloadTooLarge);
+}
+
+#[test]
+fn test_pow_adaptive() {
+    let client = client();
+
+    // check base difficulty
+    let (_, difficulty) = common::challenge(&client, "register");
+    assert_eq!(difficulty, 8);
+
+    // raise difficulty by reaching and doubling the request rate
+    for expected in [9, 10] {
+        for _ in 0..3 {
+            let resp = client.post("/api/auth/register").json(&json!({}))
+                .dispatch();
+            assert_eq!(resp.status(), Status::PaymentRequired);
+        }
+        let (_, difficulty) = common::challenge(&client, "register");
+        assert_eq!(difficulty, expected);
+    }
+
+    // check maximum difficulty
+    for _ in 0..12 {
+        client.post("/api/auth/register").json(&json!({})).dispatch();
+    }
+    let (_, difficulty) = common::challenge(&client, "register");
+    assert_eq!(difficulty, 10);
+
+    // check other scopes being unaffected
+    let (_, difficulty) = common::challenge(&client, "password_reset");
+    assert_eq!(difficulty, 4);
+}
+
+#[test]
+fn test_pow_solver() {
+    let client = client();
+
+    // get versioned solver module and its worker
+    for asset in ["solver", "worker"] {
+        let resp = client.get(format!("/assets/pow/v1/{asset}.js"))
+            .dispatch();
+        assert_eq!(resp.status(), Status::Ok);
+        assert_eq!(resp.content_type(), Some(ContentType::JavaScript));
+    }
+
+    // check body solved by the shipped worker, see fixtures/pow-solver.mjs
+    let fixture: Value = serde_json::from_str(
+        include_str!("fixtures/pow-solver.json"),
+    ).unwrap();
+    let solved = fixture["body"].as_str().unwrap();
+    let hash = Sha512::digest(solved.as_bytes());
+    assert_eq!(format!("{hash:x}"), fixture["hash"]);
+    let difficulty = fixture["difficulty"].as_u64().unwrap() as u32;
+    assert!(zeros(&hash) >= difficulty);
+
+    // check solved body holding input, challenge, and first valid nonce
+    let mut expected = fixture["input"].clone();
+    expected["challenge"] = fixture["challenge"].clone();
+    let parsed: Value = serde_json::from_str(solved).unwrap();
+    expected["nonce"] = parsed["nonce"].clone();
+    assert_eq!(parsed, expected);
+    let prefix = solved.rsplit_once(':').unwrap().0;
+    let nonce = (0..).find(|nonce| {
+        let body = format!("{prefix}:{nonce}}}");
+        zeros(&Sha512::digest(body.as_bytes())) >= difficulty
+    }).unwrap();
+    assert_eq!(parsed["nonce"], nonce);
+
+    // serialize body with non-ASCII characters like the solver module
+    let (challenge, difficulty) = common::challenge(&client, "register");
+    let json = format!(concat!(
+        r#"{{"name":"Zoë ✓","email":"zoe@example.com","#,
+        r#""password":"supersecret","challenge":"{}"}}"#,
+    ), challenge);
+    let prefix = format!("{},\"nonce\":", &json[..json.len() - 1]);
+
+    // search nonce like the worker and send the exact bytes
+    let body = (0..).map(|nonce| format!("{prefix}{nonce}}}"))
+        .find(|body| zeros(&Sha512::digest(body.as_bytes())) >= difficulty)
+        .unwrap();
+    let resp = client.post("/api/auth/register")
+        .header(ContentType::JSON).body(&body).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+}
+
+fn zeros(hash: &[u8]) -> u32 {
+    match hash.iter().position(|byte| *byte != 0) {
+        Some(i) => i as u32 * 8 + hash[i].leading_zeros(),
+        None => hash.len() as u32 * 8,
+    }
+}
+
+fn client() -> Client {
+    common::client_with(&[
+        ("API_POW_REGISTER_DIFFICULTY", "8"),
+        ("API_POW_PASSWORD_RESET_DIFFICULTY", "4"),
+        ("API_POW_BODY_LIMIT", "1024"),
+        ("API_POW_ADAPTIVE", "true"),
+        ("API_POW_ADAPTIVE_RATE", "3"),
+        ("API_POW_MAX_DIFFICULTY", "10"),
+    ])
+}
+
+#[derive(Deserialize)]
+struct ChallengeResponse {
+    challenge: String,
+    difficulty: u32,
+    expires: String,
+}
@@ -0,0 +1,62 @@
+use rocket::http::{ContentType, Status};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+mod common;
+
+#[test]
+fn test_validation_problem() {
+    let client = common::client();
+
+    // login with invalid email
+    let resp = client.post("/api/auth/login").json(&json!({
+        "email": "alice",
+        "password": "supersecret",
+    })).dispatch();
+    assert_eq!(resp.status(), Status::UnprocessableEntity);
+    assert_eq!(resp.content_type(), Some(problem_json()));
+
+    // check problem details and field errors
+    let problem: Problem = resp.into_json().unwrap();
+    assert_eq!(problem.status, 422);
+    assert_eq!(problem.code, "validation_failed");
+    assert_eq!(problem.errors.unwrap()["email"][0]["code"], "email");
+}
+
+#[test]
+fn test_catcher_problem() {
+    let client = common::client();
+
+    // access protected route without token
+    let resp = client.get("/api/users").dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+    assert_eq!(resp.content_type(), Some(problem_json()));
+    let problem: Problem = resp.into_json().unwrap();
+    assert_eq!(problem.code, "unauthorized");
+    assert_eq!(problem.title, "Unauthorized");
+
+    // access non-existent API route
+    let resp = client.get("/api/non-existent-path").dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+    assert_eq!(resp.content_type(), Some(problem_json()));
+    let problem: Problem = resp.into_json().unwrap();
+    assert_eq!(problem.code, "not_found");
+
+    // send malformed body
+    let resp = client.post("/api/auth/login")
+        .header(ContentType::JSON).body("{").dispatch();
+    assert_eq!(resp.status(), Status::BadRequest);
+    assert_eq!(resp.content_type(), Some(problem_json()));
+}
+
+fn problem_json() -> ContentType {
+    ContentType::new("application", "problem+json")
+}
+
+#[derive(Deserialize)]
+struct Problem {
+    title: String,
+    status: u16,
+    code: String,
+    errors: Option<Value>,
+}
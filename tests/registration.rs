@@ -0,0 +1,140 @@
+use rocket::{http::{Header, Status}, local::blocking::Client};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+mod common;
+
+// policies share the process environment and are therefore tested in sequence
+#[test]
+fn test_registration_policies() {
+    closed();
+    domains();
+    invite();
+}
+
+fn closed() {
+    let client = common::client_with(&[
+        ("API_REGISTRATION", "closed"), ("OPENAPI_ENABLE", "true"),
+    ]);
+
+    // try registering
+    let resp = register(&client, "alice@example.com", None);
+    assert_eq!(resp.0, Status::Forbidden);
+    assert_eq!(resp.1["code"], "registration_closed");
+
+    // check policy in OpenAPI description
+    let openapi: Value = client.get("/api-docs/openapi.json").dispatch()
+        .into_json().unwrap();
+    let description = openapi["paths"]["/api/auth/register"]["post"]
+        ["description"].as_str().unwrap();
+    assert!(description.contains("Registration on this server is closed."));
+}
+
+fn domains() {
+    let client = common::client_with(&[
+        ("API_REGISTRATION", "domains"),
+        ("API_REGISTRATION_DOMAINS", "[example.com,example.org]"),
+    ]);
+
+    // try registering with other domains
+    for email in ["alice@example.net", "alice@sub.example.com"] {
+        let resp = register(&client, email, None);
+        assert_eq!(resp.0, Status::Forbidden);
+        assert_eq!(resp.1["code"], "domain_not_allowed");
+    }
+
+    // register with allowed domains
+    let resp = register(&client, "alice@EXAMPLE.org", None);
+    assert_eq!(resp.0, Status::NoContent);
+    common::mailer(&client).receive_dummy().unwrap();
+
+    // register existing email address without leaking it
+    let resp = register(&client, "owner@example.com", None);
+    assert_eq!(resp.0, Status::NoContent);
+}
+
+fn invite() {
+    let client = common::client_with(&[
+        ("API_REGISTRATION", "invite"), ("API_REGISTRATION_DOMAINS", "[]"),
+    ]);
+
+    // login owner
+    let login: Value = client.post("/api/auth/login").json(&json!({
+        "email": "owner@example.com", "password": "supersecret",
+    })).dispatch().into_json().unwrap();
+    let header = Header::new(
+        "Authorization", format!("apikey {}", login["token"].as_str().unwrap()),
+    );
+
+    // try registering without and with unknown invite code
+    let code = "12345678911131517192123252729310";
+    for invite in [None, Some(code)] {
+        let resp = register(&client, "alice@example.com", invite);
+        assert_eq!(resp.0, Status::Forbidden);
+        assert_eq!(resp.1["code"], "invalid_invite_code");
+    }
+
+    // try creating invite code without login
+    let resp = client.post("/api/invites").json(&json!({})).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    // try creating invite code valid for more than a year
+    let resp = client.post("/api/invites").header(header.clone())
+        .json(&json!({ "lifetime": 40000000 })).dispatch();
+    assert_eq!(resp.status(), Status::UnprocessableEntity);
+
+    // create invite code for two registrations
+    let resp = client.post("/api/invites").header(header.clone())
+        .json(&json!({ "max_uses": 2 })).dispatch();
+    assert_eq!(resp.status(), Status::Created);
+    let invite: InviteCodeResponse = resp.into_json().unwrap();
+    assert_eq!(invite.code.len(), 32);
+
+    // register with invite code, also for an existing email address
+    let resp = register(&client, "alice@example.com", Some(&invite.code));
+    assert_eq!(resp.0, Status::NoContent);
+    common::mailer(&client).receive_dummy().unwrap();
+    let resp = register(&client, "owner@example.com", Some(&invite.code));
+    assert_eq!(resp.0, Status::NoContent);
+
+    // list used up invite codes
+    let resp = client.get("/api/invites").header(header.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let invites: Vec<Value> = resp.into_json().unwrap();
+    assert!(invites.is_empty());
+
+    // try registering with used up invite code
+    let resp = register(&client, "bob@example.com", Some(&invite.code));
+    assert_eq!(resp.0, Status::Forbidden);
+
+    // revoke unused invite code
+    let resp = client.post("/api/invites").header(header.clone())
+        .json(&json!({})).dispatch();
+    let invite: InviteCodeResponse = resp.into_json().unwrap();
+    let resp = client.get("/api/invites").header(header.clone()).dispatch();
+    let invites: Vec<Value> = resp.into_json().unwrap();
+    assert_eq!(invites.len(), 1);
+    assert_eq!(invites[0]["id"], invite.id);
+    let resp = client.delete(format!("/api/invites/{}", invite.id))
+        .header(header.clone()).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+    let resp = register(&client, "bob@example.com", Some(&invite.code));
+    assert_eq!(resp.0, Status::Forbidden);
+}
+
+fn register(
+    client: &Client, email: &str, invite: Option<&str>,
+) -> (Status, Value) {
+    let body = common::solve(client, "register", json!({
+        "name": "Alice", "email": email, "password": "supersecret",
+        "invite": invite,
+    }));
+    let resp = client.post("/api/auth/register").json(&body).dispatch();
+    (resp.status(), resp.into_json().unwrap_or(Value::Null))
+}
+
+#[derive(Deserialize)]
+struct InviteCodeResponse {
+    id: String,
+    code: String,
+}
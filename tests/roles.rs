@@ -0,0 +1,142 @@
+use rocket::{
+    http::{Header, Status}, local::blocking::{Client, LocalResponse},
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+mod common;
+
+#[test]
+fn test_roles() {
+    let client = common::client();
+    let login: LoginResponse = client.post("/api/auth/login").json(&json!({
+        "email": "owner@example.com", "password": "supersecret",
+    })).dispatch().into_json().unwrap();
+    let owner = header(&login);
+
+    // register users and make one an admin
+    let alice: LoginResponse =
+        common::register(&client, "Alice", "alice@example.com");
+    let bob: LoginResponse = common::register(&client, "Bob", "bob@example.com");
+    let resp = client.patch(format!("/api/users/{}", bob.id))
+        .header(owner.clone()).json(&json!({ "role": "admin" })).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let admin = header(&bob);
+
+    // list built-in roles
+    let resp = client.get("/api/roles").header(owner.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let roles: Vec<RoleResponse> = resp.into_json().unwrap();
+    let names: Vec<_> = roles.iter().map(|role| role.name.as_str()).collect();
+    assert_eq!(names, ["admin", "org_admin", "org_owner", "owner", "user"]);
+    assert!(roles.iter().all(|role| role.builtin));
+
+    // try managing roles as admin
+    let resp = client.get("/api/roles").header(admin.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = create(&client, &admin, json!({
+        "name": "moderator", "permissions": ["users:list"],
+    }));
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    // try creating roles with invalid name and unknown permission
+    for body in [
+        json!({ "name": "Moderator!", "permissions": [] }),
+        json!({ "name": "moderator", "permissions": ["users:fly"] }),
+    ] {
+        let resp = create(&client, &owner, body);
+        assert_eq!(resp.status(), Status::UnprocessableEntity);
+    }
+
+    // create custom role and try creating it twice
+    let body = json!({
+        "name": "moderator", "permissions": ["users:list", "users:suspend"],
+    });
+    let resp = create(&client, &owner, body.clone());
+    assert_eq!(resp.status(), Status::Created);
+    let role: RoleResponse = resp.into_json().unwrap();
+    assert_eq!(role.name, "moderator");
+    assert_eq!(role.permissions, ["users:list", "users:suspend"]);
+    assert!(!role.builtin);
+    let resp = create(&client, &owner, body);
+    assert_eq!(resp.status(), Status::Conflict);
+
+    // try assigning custom role as admin and assign it as owner
+    let resp = client.patch(format!("/api/users/{}", alice.id))
+        .header(admin.clone()).json(&json!({ "role": "moderator" }))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = client.patch(format!("/api/users/{}", alice.id))
+        .header(owner.clone()).json(&json!({ "role": "moderator" }))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let moderator = header(&alice);
+
+    // use granted and missing permissions
+    let resp = client.get("/api/users").header(moderator.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.get("/api/invites").header(moderator.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    // try suspending admin, whose permissions are not a subset
+    let resp = client.post(format!("/api/users/{}/suspend", bob.id))
+        .header(moderator.clone()).json(&json!({ "reason": "Test" }))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    // update custom role and lose permission
+    let resp = client.patch("/api/roles/moderator").header(owner.clone())
+        .json(&json!({ "permissions": ["users:suspend"] })).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.get("/api/users").header(moderator.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    // try updating and deleting built-in and unknown roles
+    let resp = client.patch("/api/roles/admin").header(owner.clone())
+        .json(&json!({ "permissions": [] })).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let problem: Value = resp.into_json().unwrap();
+    assert_eq!(problem["code"], "builtin_role");
+    let resp = client.delete("/api/roles/user").header(owner.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = client.patch("/api/roles/unknown").header(owner.clone())
+        .json(&json!({ "permissions": [] })).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    // try deleting assigned role and delete it after reassigning user
+    let resp = client.delete("/api/roles/moderator").header(owner.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::Conflict);
+    let resp = client.patch(format!("/api/users/{}", alice.id))
+        .header(owner.clone()).json(&json!({ "role": "user" })).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = client.delete("/api/roles/moderator").header(owner.clone())
+        .dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+    let resp = client.delete("/api/roles/moderator").header(owner).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+}
+
+fn create<'c>(
+    client: &'c Client, header: &Header<'static>, body: Value,
+) -> LocalResponse<'c> {
+    client.post("/api/roles").header(header.clone()).json(&body).dispatch()
+}
+
+fn header(login: &LoginResponse) -> Header<'static> {
+    Header::new("Authorization", format!("apikey {}", login.token))
+}
+
+#[derive(Deserialize)]
+struct LoginResponse {
+    id: String,
+    token: String,
+}
+
+#[derive(Deserialize)]
+struct RoleResponse {
+    name: String,
+    permissions: Vec<String>,
+    builtin: bool,
+}
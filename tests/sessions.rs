@@ -0,0 +1,148 @@
+use rocket::{http::{Header, Status}, local::blocking::Client};
+use serde::Deserialize;
+use serde_json::json;
+
+mod common;
+
+#[test]
+fn test_own_sessions() {
+    let client = common::client();
+
+    // login owner twice
+    let first = login(&client, "owner@example.com", "supersecret");
+    let second = login(&client, "owner@example.com", "supersecret");
+    let header = format!("apikey {}", second.token);
+
+    // list sessions
+    let resp = client.get("/api/auth/sessions")
+        .header(Header::new("Authorization", header.clone())).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let sessions: Vec<SessionResponse> = resp.into_json().unwrap();
+    assert_eq!(sessions.len(), 2);
+    assert_eq!(sessions.iter().filter(|s| s.current).count(), 1);
+    let other = sessions.iter().find(|s| !s.current).unwrap();
+    assert_eq!(other.user_agent.as_deref(), Some("test-agent"));
+    assert_ne!(other.ip.as_deref(), Some("10.0.0.1"));
+
+    // revoke other session
+    let resp = client.delete(format!("/api/auth/sessions/{}", other.id))
+        .header(Header::new("Authorization", header.clone())).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+
+    // try revoking it again
+    let resp = client.delete(format!("/api/auth/sessions/{}", other.id))
+        .header(Header::new("Authorization", header.clone())).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    // try using revoked session
+    let resp = client.get("/api/auth/sessions")
+        .header(Header::new("Authorization", format!("apikey {}", first.token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    // logout all other sessions
+    let third = login(&client, "owner@example.com", "supersecret");
+    let resp = client.post("/api/auth/logout-all")
+        .header(Header::new("Authorization", header.clone())).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+
+    // check other session being revoked
+    let resp = client.get("/api/auth/sessions")
+        .header(Header::new("Authorization", format!("apikey {}", third.token)))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    // check current session still being valid
+    let resp = client.get("/api/auth/sessions")
+        .header(Header::new("Authorization", header)).dispatch();
+    let sessions: Vec<SessionResponse> = resp.into_json().unwrap();
+    assert_eq!(sessions.len(), 1);
+}
+
+#[test]
+fn test_user_sessions() {
+    let client = common::client();
+
+    // login owner and user
+    let owner = login(&client, "owner@example.com", "supersecret");
+    let owner_header = format!("apikey {}", owner.token);
+    let alice: LoginResponse =
+        common::register(&client, "Alice", "alice@example.com");
+    let header = format!("apikey {}", alice.token);
+
+    // try listing owner sessions
+    let resp = client.get(format!("/api/users/{}/sessions", owner.id))
+        .header(Header::new("Authorization", header.clone())).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    // try listing and revoking owner sessions as admin
+    let bob: LoginResponse =
+        common::register(&client, "Bob", "bob@example.com");
+    let resp = client.patch(format!("/api/users/{}", bob.id))
+        .header(Header::new("Authorization", owner_header.clone()))
+        .json(&json!({ "role": "admin" })).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let admin = Header::new("Authorization", format!("apikey {}", bob.token));
+    let resp = client.get(format!("/api/users/{}/sessions", owner.id))
+        .header(admin.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = client.delete(format!("/api/users/{}/sessions", owner.id))
+        .header(admin.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = client.get("/api/auth/sessions")
+        .header(Header::new("Authorization", owner_header.clone())).dispatch();
+    let sessions: Vec<SessionResponse> = resp.into_json().unwrap();
+    let resp = client.delete(
+        format!("/api/users/{}/sessions/{}", owner.id, sessions[0].id)
+    ).header(admin.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = client.get(format!("/api/users/{}/sessions", alice.id))
+        .header(admin).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // list user sessions as owner
+    let resp = client.get(format!("/api/users/{}/sessions", alice.id))
+        .header(Header::new("Authorization", owner_header.clone())).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let sessions: Vec<SessionResponse> = resp.into_json().unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert!(!sessions[0].current);
+
+    // try revoking non-existent session
+    let resp = client.delete(
+        format!("/api/users/{}/sessions/non-existent-session", alice.id)
+    ).header(Header::new("Authorization", owner_header.clone())).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+
+    // revoke all user sessions
+    let resp = client.delete(format!("/api/users/{}/sessions", alice.id))
+        .header(Header::new("Authorization", owner_header)).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+
+    // try using revoked session
+    let resp = client.get(format!("/api/users/{}", alice.id))
+        .header(Header::new("Authorization", header)).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+}
+
+fn login(client: &Client, email: &str, password: &str) -> LoginResponse {
+    client.post("/api/auth/login")
+        .header(Header::new("User-Agent", "test-agent"))
+        .header(Header::new("X-Real-IP", "10.0.0.1"))
+        .json(&json!({ "email": email, "password": password }))
+        .dispatch().into_json().unwrap()
+}
+
+#[derive(Deserialize)]
+struct LoginResponse {
+    id: String,
+    token: String,
+}
+
+#[derive(Deserialize)]
+struct SessionResponse {
+    id: String,
+    current: bool,
+    ip: Option<String>,
+    user_agent: Option<String>,
+}
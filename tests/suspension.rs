@@ -0,0 +1,126 @@
+use rocket::{
+    http::{Header, Status}, local::blocking::{Client, LocalResponse},
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+mod common;
+
+#[test]
+fn test_suspension() {
+    let client = common::client();
+    let owner = login(&client, "owner@example.com").into_json::<LoginResponse>()
+        .unwrap();
+    let owner = header(&owner);
+
+    // register users and make one an admin
+    let alice: LoginResponse =
+        common::register(&client, "Alice", "alice@example.com");
+    let bob: LoginResponse = common::register(&client, "Bob", "bob@example.com");
+    let resp = client.patch(format!("/api/users/{}", bob.id))
+        .header(owner.clone()).json(&json!({ "role": "admin" })).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let admin = header(&bob);
+
+    // try suspending as regular user, suspending owner, and suspending self
+    let resp = suspend(&client, &header(&alice), &bob.id, json!({
+        "reason": "Testing",
+    }));
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = client.get("/api/users").header(owner.clone()).dispatch();
+    let owner_id = resp.into_json::<Value>().unwrap()["items"].as_array()
+        .unwrap().iter().find(|user| user["role"] == "owner").unwrap()["id"]
+        .as_str().unwrap().to_string();
+    let resp = suspend(&client, &admin, &owner_id, json!({ "reason": "Test" }));
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = suspend(&client, &admin, &bob.id, json!({ "reason": "Test" }));
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    // try suspending with end in the past
+    let resp = suspend(&client, &admin, &alice.id, json!({
+        "reason": "Spamming", "until": "2020-01-01T00:00:00Z",
+    }));
+    assert_eq!(resp.status(), Status::UnprocessableEntity);
+
+    // suspend user as admin
+    let resp = suspend(&client, &admin, &alice.id, json!({
+        "reason": "Spamming", "until": "2099-01-01T00:00:00Z",
+    }));
+    assert_eq!(resp.status(), Status::Ok);
+    let user: Value = resp.into_json().unwrap();
+    assert_eq!(user["disabled"]["reason"], "Spamming");
+    assert_eq!(user["disabled"]["until"], "2099-01-01T00:00:00Z");
+
+    // receive suspension notification
+    let email = common::mailer(&client).receive_dummy().unwrap();
+    assert_eq!(email.envelope().to()[0].to_string(), "alice@example.com");
+    let content = String::from_utf8(email.formatted()).unwrap();
+    assert!(content.contains("following reason: Spamming"));
+
+    // try using revoked session and logging in
+    let resp = client.get(format!("/api/users/{}", alice.id))
+        .header(header(&alice)).dispatch();
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let resp = login(&client, "alice@example.com");
+    assert_eq!(resp.status(), Status::Forbidden);
+    let problem: Value = resp.into_json().unwrap();
+    assert_eq!(problem["code"], "account_suspended");
+    assert!(problem["detail"].as_str().unwrap().contains("Spamming"));
+
+    // try suspending admin as admin and suspend them as owner
+    let resp = unsuspend(&client, &admin, &bob.id);
+    assert_eq!(resp.status(), Status::Forbidden);
+    let resp = suspend(&client, &owner, &bob.id, json!({ "reason": "Test" }));
+    assert_eq!(resp.status(), Status::Ok);
+    common::mailer(&client).receive_dummy().unwrap();
+    let resp = unsuspend(&client, &admin, &alice.id);
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    // reactivate user and login again
+    let resp = unsuspend(&client, &owner, &alice.id);
+    assert_eq!(resp.status(), Status::Ok);
+    let user: Value = resp.into_json().unwrap();
+    assert!(user.get("disabled").is_none());
+    let email = common::mailer(&client).receive_dummy().unwrap();
+    let content = String::from_utf8(email.formatted()).unwrap();
+    assert!(content.contains("reactivated"));
+    let resp = login(&client, "alice@example.com");
+    assert_eq!(resp.status(), Status::Ok);
+
+    // allow login after suspension ended
+    let _: Vec<Value> = common::query(&client, &format!("
+        UPDATE user:⟨{}⟩ SET disabled.until = time::now() - 1s
+    ", bob.id));
+    let resp = login(&client, "bob@example.com");
+    assert_eq!(resp.status(), Status::Ok);
+}
+
+fn suspend<'c>(
+    client: &'c Client, header: &Header<'static>, id: &str, body: Value,
+) -> LocalResponse<'c> {
+    client.post(format!("/api/users/{id}/suspend"))
+        .header(header.clone()).json(&body).dispatch()
+}
+
+fn unsuspend<'c>(
+    client: &'c Client, header: &Header<'static>, id: &str,
+) -> LocalResponse<'c> {
+    client.post(format!("/api/users/{id}/unsuspend"))
+        .header(header.clone()).dispatch()
+}
+
+fn login<'c>(client: &'c Client, email: &str) -> LocalResponse<'c> {
+    client.post("/api/auth/login")
+        .json(&json!({ "email": email, "password": "supersecret" }))
+        .dispatch()
+}
+
+fn header(login: &LoginResponse) -> Header<'static> {
+    Header::new("Authorization", format!("apikey {}", login.token))
+}
+
+#[derive(Deserialize)]
+struct LoginResponse {
+    id: String,
+    token: String,
+}
@@ -0,0 +1,277 @@
+use backend_template::totp;
+use rocket::{
+    http::{Header, Status}, local::blocking::{Client, LocalResponse},
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+mod common;
+
+#[test]
+fn test_totp() {
+    // check test vectors of RFC 6238 using a fixed clock
+    let secret = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+    assert_eq!(totp::code(secret, 59).unwrap(), "287082");
+    assert_eq!(totp::code(secret, 1111111109).unwrap(), "081804");
+    assert_eq!(totp::code(secret, 1234567890).unwrap(), "005924");
+
+    // check accepted clock drift
+    let step = 1234567890 / totp::PERIOD;
+    assert_eq!(totp::verify(secret, "005924", 1234567890), Some(step));
+    assert_eq!(totp::verify(secret, "005924", 1234567890 + 30), Some(step));
+    assert_eq!(totp::verify(secret, "005924", 1234567890 - 30), Some(step));
+    assert_eq!(totp::verify(secret, "005924", 1234567890 + 60), None);
+    assert_eq!(totp::verify(secret, "invalid", 1234567890), None);
+    assert_eq!(totp::verify("invalid secret", "005924", 1234567890), None);
+}
+
+#[test]
+fn test_two_factor() {
+    let client = client();
+
+    // register user
+    let alice: LoginResponse =
+        common::register(&client, "Alice", "alice@example.com");
+    let header = format!("apikey {}", alice.token);
+
+    // set up two-factor authentication
+    let resp = client.post("/api/auth/2fa/setup")
+        .header(Header::new("Authorization", header.clone())).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let setup: SetupResponse = resp.into_json().unwrap();
+    assert!(setup.uri.starts_with("otpauth://totp/Backend:alice"));
+    assert!(setup.uri.contains(&format!("secret={}", setup.secret)));
+
+    // try enabling with invalid code
+    let code = wrong_code(&setup.secret);
+    let resp = client.post("/api/auth/2fa/enable")
+        .json(&json!({ "code": code }))
+        .header(Header::new("Authorization", header.clone())).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    // enable two-factor authentication
+    let code = totp::code(&setup.secret, totp::now()).unwrap();
+    let resp = client.post("/api/auth/2fa/enable")
+        .json(&json!({ "code": code }))
+        .header(Header::new("Authorization", header.clone())).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let codes: RecoveryCodesResponse = resp.into_json().unwrap();
+    assert_eq!(codes.codes.len(), 10);
+
+    // check recovery codes being stored hashed
+    let stored: Vec<String> =
+        common::query(&client, "SELECT VALUE code FROM recovery_code");
+    assert_eq!(stored.len(), 10);
+    assert!(stored.iter().all(|code| !codes.codes.contains(code)));
+
+    // try setting up again
+    let resp = client.post("/api/auth/2fa/setup")
+        .header(Header::new("Authorization", header.clone())).dispatch();
+    assert_eq!(resp.status(), Status::Conflict);
+
+    // login and verify with wrong code
+    let pending = challenge(&client);
+    let resp = verify(&client, &pending, &wrong_code(&setup.secret));
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let problem: Value = resp.into_json().unwrap();
+    assert_eq!(problem["code"], "invalid_code");
+
+    // try replaying code used for enabling
+    let resp = verify(&client, &pending, &code);
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    // verify with code of next time step
+    let code = totp::code(&setup.secret, totp::now() + totp::PERIOD).unwrap();
+    let resp = verify(&client, &pending, &code);
+    assert_eq!(resp.status(), Status::Ok);
+    let session: LoginResponse = resp.into_json().unwrap();
+    assert_eq!(session.id, alice.id);
+
+    // try reusing challenge and code
+    let resp = verify(&client, &pending, &code);
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let resp = verify(&client, &challenge(&client), &code);
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    // verify with recovery code and try reusing it
+    let resp = verify(&client, &challenge(&client), &codes.codes[0]);
+    assert_eq!(resp.status(), Status::Ok);
+    let resp = verify(&client, &challenge(&client), &codes.codes[0]);
+    assert_eq!(resp.status(), Status::Unauthorized);
+
+    // exhaust attempts of challenge
+    let pending = challenge(&client);
+    for _ in 0..5 {
+        let resp = verify(&client, &pending, &wrong_code(&setup.secret));
+        assert_eq!(resp.status(), Status::Unauthorized);
+    }
+    let resp = verify(&client, &pending, &codes.codes[1]);
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let problem: Value = resp.into_json().unwrap();
+    assert_eq!(problem["code"], "invalid_challenge");
+
+    // disable two-factor authentication with recovery code
+    let resp = client.post("/api/auth/2fa/disable")
+        .json(&json!({ "code": codes.codes[1] }))
+        .header(Header::new("Authorization", header.clone())).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+    let stored: Vec<String> =
+        common::query(&client, "SELECT VALUE code FROM recovery_code");
+    assert!(stored.is_empty());
+
+    // login without second factor
+    let resp = login(&client, "alice@example.com");
+    assert_eq!(resp.status(), Status::Ok);
+}
+
+#[test]
+fn test_force_disable() {
+    let client = client();
+
+    // register user and enable two-factor authentication
+    let alice: LoginResponse =
+        common::register(&client, "Alice", "alice@example.com");
+    let header = format!("apikey {}", alice.token);
+    let resp = client.post("/api/auth/2fa/setup")
+        .header(Header::new("Authorization", header.clone())).dispatch();
+    let setup: SetupResponse = resp.into_json().unwrap();
+    let code = totp::code(&setup.secret, totp::now()).unwrap();
+    let resp = client.post("/api/auth/2fa/enable")
+        .json(&json!({ "code": code }))
+        .header(Header::new("Authorization", header.clone())).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let pending = challenge(&client);
+
+    // try force-disabling as user
+    let resp = client.delete(format!("/api/users/{}/2fa", alice.id))
+        .header(Header::new("Authorization", header)).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    // force-disable as owner
+    let resp = login(&client, "owner@example.com");
+    let owner: LoginResponse = resp.into_json().unwrap();
+    let owner_header = format!("apikey {}", owner.token);
+    let resp = client.delete(format!("/api/users/{}/2fa", alice.id))
+        .header(Header::new("Authorization", owner_header.clone())).dispatch();
+    assert_eq!(resp.status(), Status::NoContent);
+
+    // try force-disabling for non-existent user and for self
+    let resp = client.delete("/api/users/non-existent-user/2fa")
+        .header(Header::new("Authorization", owner_header.clone())).dispatch();
+    assert_eq!(resp.status(), Status::NotFound);
+    let resp = client.delete(format!("/api/users/{}/2fa", owner.id))
+        .header(Header::new("Authorization", owner_header.clone())).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    // try force-disabling owner with permission but without outranking them
+    let resp = client.post("/api/roles")
+        .header(Header::new("Authorization", owner_header.clone()))
+        .json(&json!({ "name": "support", "permissions": ["users:2fa_reset"] }))
+        .dispatch();
+    assert_eq!(resp.status(), Status::Created);
+    let resp = client.patch(format!("/api/users/{}", alice.id))
+        .header(Header::new("Authorization", owner_header))
+        .json(&json!({ "role": "support" })).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let support: LoginResponse =
+        login(&client, "alice@example.com").into_json().unwrap();
+    let header = format!("apikey {}", support.token);
+    let resp = client.delete(format!("/api/users/{}/2fa", owner.id))
+        .header(Header::new("Authorization", header)).dispatch();
+    assert_eq!(resp.status(), Status::Forbidden);
+
+    // check open challenge being deleted and login without second factor
+    let code = totp::code(&setup.secret, totp::now() + totp::PERIOD).unwrap();
+    let resp = verify(&client, &pending, &code);
+    assert_eq!(resp.status(), Status::Unauthorized);
+    let resp = login(&client, "alice@example.com");
+    assert_eq!(resp.status(), Status::Ok);
+}
+
+#[test]
+fn test_two_factor_lockout() {
+    let client = client();
+
+    // register user and enable two-factor authentication
+    let alice: LoginResponse =
+        common::register(&client, "Alice", "alice@example.com");
+    let header = format!("apikey {}", alice.token);
+    let resp = client.post("/api/auth/2fa/setup")
+        .header(Header::new("Authorization", header.clone())).dispatch();
+    let setup: SetupResponse = resp.into_json().unwrap();
+    let code = totp::code(&setup.secret, totp::now()).unwrap();
+    let resp = client.post("/api/auth/2fa/enable")
+        .json(&json!({ "code": code }))
+        .header(Header::new("Authorization", header)).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+
+    // guess codes with fresh challenges, each counting password and code
+    for _ in 0..5 {
+        let code = wrong_code(&setup.secret);
+        let resp = verify(&client, &challenge(&client), &code);
+        assert_eq!(resp.status(), Status::Unauthorized);
+    }
+
+    // receive lockout notification and try logging in again
+    let email = common::mailer(&client).receive_dummy().unwrap();
+    assert_eq!(email.envelope().to()[0].to_string(), "alice@example.com");
+    let content = String::from_utf8(email.formatted()).unwrap();
+    assert!(content.contains("temporarily locked"));
+    let resp = login(&client, "alice@example.com");
+    assert_eq!(resp.status(), Status::TooManyRequests);
+}
+
+fn client() -> Client {
+    common::client_with(&[("API_LOGIN_MAX_FAILURES", "10")])
+}
+
+fn login<'c>(client: &'c Client, email: &str) -> LocalResponse<'c> {
+    client.post("/api/auth/login")
+        .json(&json!({ "email": email, "password": "supersecret" }))
+        .dispatch()
+}
+
+fn challenge(client: &Client) -> String {
+    let resp = login(client, "alice@example.com");
+    assert_eq!(resp.status(), Status::Accepted);
+    let challenge: ChallengeResponse = resp.into_json().unwrap();
+    challenge.challenge
+}
+
+fn verify<'c>(
+    client: &'c Client, challenge: &str, code: &str,
+) -> LocalResponse<'c> {
+    client.post("/api/auth/2fa/verify")
+        .json(&json!({ "challenge": challenge, "code": code }))
+        .dispatch()
+}
+
+fn wrong_code(secret: &str) -> String {
+    let now = totp::now();
+    (0..1_000_000).map(|n| format!("{n:06}"))
+        .find(|code| totp::verify(secret, code, now).is_none()
+            && totp::verify(secret, code, now + totp::PERIOD).is_none())
+        .unwrap()
+}
+
+#[derive(Deserialize)]
+struct LoginResponse {
+    id: String,
+    token: String,
+}
+
+#[derive(Deserialize)]
+struct ChallengeResponse {
+    challenge: String,
+}
+
+#[derive(Deserialize)]
+struct SetupResponse {
+    secret: String,
+    uri: String,
+}
+
+#[derive(Deserialize)]
+struct RecoveryCodesResponse {
+    codes: Vec<String>,
+}
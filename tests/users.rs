@@ -70,11 +70,11 @@ fn test_user() {
         .header(Header::new("Authorization", header.clone())).dispatch();
     assert_eq!(resp.status(), Status::NoContent);
 
-    // try logging in
+    // try logging in while pending deletion
     let resp = client.post("/api/auth/login").json(&json!({
         "email": "alice@example.com", "password": "supersecret",
     })).dispatch();
-    assert_eq!(resp.status(), Status::Unauthorized);
+    assert_eq!(resp.status(), Status::Forbidden);
 }
 
 #[test]
@@ -100,8 +100,9 @@ fn test_admin() {
     let resp = client.get("/api/users")
         .header(Header::new("Authorization", header.clone())).dispatch();
     assert_eq!(resp.status(), Status::Ok);
-    let users: Vec<UserResponse> = resp.into_json().unwrap();
-    assert_eq!(users.len(), 2);
+    let users: PageResponse = resp.into_json().unwrap();
+    assert_eq!(users.items.len(), 2);
+    assert_eq!(users.total, 2);
 
     // try getting non-existent user
     let resp = client.get("/api/users/non-existent-user")
@@ -163,18 +164,75 @@ fn test_owner() {
     assert_eq!(resp.status(), Status::Unauthorized);
 }
 
+#[test]
+fn test_user_listing() {
+    let client = common::client();
+
+    // login owner and register users
+    let owner = login(&client, "owner@example.com", "supersecret");
+    let header = Header::new("Authorization", format!("apikey {}", owner.token));
+    for (name, email) in [
+        ("Bob", "bob@example.com"), ("Carol", "carol@example.com"),
+        ("Dave", "dave@example.org"),
+    ] {
+        let _: LoginResponse = common::register(&client, name, email);
+    }
+
+    // list first page sorted by name
+    let resp = client.get("/api/users?limit=2")
+        .header(header.clone()).dispatch();
+    assert_eq!(resp.status(), Status::Ok);
+    let page: PageResponse = resp.into_json().unwrap();
+    assert_eq!(names(&page), ["Bob", "Carol"]);
+    assert_eq!((page.total, page.limit, page.offset), (4, 2, 0));
+
+    // list second page
+    let resp = client.get("/api/users?limit=2&offset=2")
+        .header(header.clone()).dispatch();
+    let page: PageResponse = resp.into_json().unwrap();
+    assert_eq!(names(&page), ["Dave", "Owner"]);
+
+    // sort by creation date in descending order
+    let resp = client.get("/api/users?sort=created&order=desc")
+        .header(header.clone()).dispatch();
+    let page: PageResponse = resp.into_json().unwrap();
+    assert_eq!(names(&page), ["Dave", "Carol", "Bob", "Owner"]);
+
+    // filter by role and by text
+    let resp = client.get("/api/users?role=owner")
+        .header(header.clone()).dispatch();
+    let page: PageResponse = resp.into_json().unwrap();
+    assert_eq!(names(&page), ["Owner"]);
+    let resp = client.get("/api/users?q=EXAMPLE.ORG")
+        .header(header.clone()).dispatch();
+    let page: PageResponse = resp.into_json().unwrap();
+    assert_eq!(names(&page), ["Dave"]);
+    assert_eq!(page.total, 1);
+
+    // try invalid query parameters
+    for query in ["limit=0", "limit=101", "offset=-1", "sort=email"] {
+        let resp = client.get(format!("/api/users?{query}"))
+            .header(header.clone()).dispatch();
+        assert_eq!(resp.status(), Status::UnprocessableEntity, "{query}");
+    }
+}
+
+fn names(page: &PageResponse) -> Vec<&str> {
+    page.items.iter().map(|user| user.name.as_str()).collect()
+}
+
 fn register_alice(client: &Client) -> LoginResponse {
     // register
-    let resp = client.post("/api/auth/register").json(&json!({
+    let body = common::solve(client, "register", json!({
         "name": "Alice",
         "email": "alice@example.com",
         "password": "supersecret",
-        "nonce": 143970,
-    })).dispatch();
+    }));
+    let resp = client.post("/api/auth/register").json(&body).dispatch();
     assert_eq!(resp.status(), Status::NoContent);
 
     // receive email and extract token
-    let email = common::mailer(&client).receive_dummy().unwrap();
+    let email = common::mailer(client).receive_dummy().unwrap();
     let content = String::from_utf8(email.formatted()).unwrap();
     let token = content.split("\r\n")
         .find(|l| l.starts_with("Your registration token:")).unwrap()
@@ -186,7 +244,7 @@ fn register_alice(client: &Client) -> LoginResponse {
         .into_json().unwrap();
 
     // consume confirmation email
-    common::mailer(&client).receive_dummy().unwrap();
+    common::mailer(client).receive_dummy().unwrap();
 
     // return login object
     result
@@ -206,6 +264,14 @@ struct LoginResponse {
     token: String,
 }
 
+#[derive(Deserialize)]
+struct PageResponse {
+    items: Vec<UserResponse>,
+    total: u64,
+    limit: u32,
+    offset: u32,
+}
+
 #[derive(Deserialize)]
 struct UserResponse {
     name: String,